@@ -0,0 +1,136 @@
+//! Velocity & net acceleration arrows drawn from each body, for checking initial conditions
+pub mod render;
+
+use cgmath::*;
+use compute::net_acceleration;
+use camera::Camera;
+use state::State;
+
+pub const VELOCITY_COLOR: [f32; 4] = [0.35, 0.9, 0.45, 0.9];
+pub const ACCELERATION_COLOR: [f32; 4] = [1.0, 0.4, 0.3, 0.9];
+/// Arrow head size in pixels
+const HEAD_PIXELS: f32 = 8.0;
+
+/// How a velocity or acceleration magnitude maps to world arrow length
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArrowScale {
+    /// `factor × magnitude`
+    Linear(f64),
+    /// `factor × ln(1 + magnitude)`, to compare very different magnitudes
+    Log(f64),
+}
+
+impl Default for ArrowScale {
+    fn default() -> ArrowScale {
+        ArrowScale::Linear(1.0)
+    }
+}
+
+impl ArrowScale {
+    /// Parses `linear` or `log`, optionally with a factor ie `log:2.5`
+    pub fn parse(spec: &str) -> Result<ArrowScale, String> {
+        let mut parts = spec.splitn(2, ':');
+        let kind = parts.next().unwrap_or("");
+        let factor = match parts.next() {
+            Some(factor) => match factor.parse::<f64>() {
+                Ok(factor) if factor > 0.0 => factor,
+                _ => return Err(format!("Invalid arrow scale factor `{}`", factor)),
+            },
+            None => 1.0,
+        };
+        match kind {
+            "linear" => Ok(ArrowScale::Linear(factor)),
+            "log" => Ok(ArrowScale::Log(factor)),
+            _ => Err(format!("Unknown arrow scale `{}`, expected linear or log", kind)),
+        }
+    }
+
+    pub fn length(&self, magnitude: f64) -> f64 {
+        match *self {
+            ArrowScale::Linear(factor) => factor * magnitude,
+            ArrowScale::Log(factor) => factor * magnitude.ln_1p(),
+        }
+    }
+
+    /// Switches between linear & log, keeping the factor
+    pub fn toggled(self) -> ArrowScale {
+        match self {
+            ArrowScale::Linear(factor) => ArrowScale::Log(factor),
+            ArrowScale::Log(factor) => ArrowScale::Linear(factor),
+        }
+    }
+}
+
+/// World line segments of an arrow from the surface of a body, with its head
+pub fn arrow_lines(center: Vector2<f64>, radius: f64, vector: Vector2<f64>, scale: ArrowScale, head: f64)
+    -> Vec<(Vector2<f64>, Vector2<f64>)>
+{
+    let magnitude = vector.magnitude();
+    let length = scale.length(magnitude);
+    if magnitude == 0.0 || length <= 0.0 {
+        return Vec::new();
+    }
+    let dir = vector / magnitude;
+    let tail = center + dir * radius;
+    let tip = tail + dir * length;
+    let head = head.min(length);
+    let side = Vector2::new(-dir.y, dir.x) * head * 0.5;
+    vec!((tail, tip), (tip, tip - dir * head + side), (tip, tip - dir * head - side))
+}
+
+/// Colored line segments of every body's velocity & acceleration arrows
+pub fn body_arrows(state: &State, camera: &Camera) -> Vec<(Vector2<f64>, Vector2<f64>, [f32; 4])> {
+    let bodies = &state.drawables.orbit_bodies;
+    let head = f64::from(HEAD_PIXELS * camera.world_per_pixel());
+    let mut lines = Vec::new();
+    for (idx, body) in bodies.iter().enumerate() {
+        let velocity = arrow_lines(body.center, body.radius, body.velocity, state.arrow_scale, head);
+        lines.extend(velocity.into_iter().map(|(a, b)| (a, b, VELOCITY_COLOR)));
+        let acceleration = net_acceleration(bodies, idx);
+        let acceleration = arrow_lines(body.center, body.radius, acceleration, state.arrow_scale, head);
+        lines.extend(acceleration.into_iter().map(|(a, b)| (a, b, ACCELERATION_COLOR)));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod arrows_test {
+    use super::*;
+
+    #[test]
+    fn parse_scale() {
+        assert_eq!(ArrowScale::parse("linear"), Ok(ArrowScale::Linear(1.0)));
+        assert_eq!(ArrowScale::parse("log:2.5"), Ok(ArrowScale::Log(2.5)));
+        assert!(ArrowScale::parse("log:-1").is_err());
+        assert!(ArrowScale::parse("cubic").is_err());
+        assert_eq!(ArrowScale::Log(2.0).toggled(), ArrowScale::Linear(2.0));
+    }
+
+    #[test]
+    fn scale_lengths() {
+        assert_eq!(ArrowScale::Linear(2.0).length(3.0), 6.0);
+        assert_eq!(ArrowScale::Log(2.0).length(0.0), 0.0);
+        assert!((ArrowScale::Log(1.0).length(::std::f64::consts::E - 1.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn arrow_from_surface() {
+        let lines = arrow_lines((1.0, 1.0).into(), 0.5, (0.0, 4.0).into(), ArrowScale::Linear(0.5), 0.2);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], ((1.0, 1.5).into(), (1.0, 3.5).into()));
+        assert!((lines[1].1 - Vector2::new(0.9, 3.3)).magnitude() < 1e-12);
+        assert!((lines[2].1 - Vector2::new(1.1, 3.3)).magnitude() < 1e-12);
+
+        assert!(arrow_lines((0.0, 0.0).into(), 1.0, Vector2::zero(), ArrowScale::default(), 0.1).is_empty());
+    }
+
+    #[test]
+    fn arrows_per_body() {
+        let state = State::new(100, 100);
+        let lines = body_arrows(&state, state.camera());
+        // velocity & acceleration arrows for each initial body
+        assert_eq!(lines.len(), state.drawables.orbit_bodies.len() * 2 * 3);
+        assert_eq!(lines[0].2, VELOCITY_COLOR);
+        assert_eq!(lines[3].2, ACCELERATION_COLOR);
+    }
+}
@@ -0,0 +1,34 @@
+use super::*;
+use super::super::{UserViewTransform, ColorFormat, DepthFormat};
+use lines::render::{LineBrush, LineVertex};
+use gfx::*;
+
+/// in front of bodies
+const ARROW_DEPTH: f32 = 0.4;
+
+pub struct ArrowBrush<R: Resources, F: Factory<R>> {
+    lines: LineBrush<R, F>,
+}
+
+impl<R: Resources, F: Factory<R> + Clone> ArrowBrush<R, F> {
+    pub fn new(factory: F,
+               target: &handle::RenderTargetView<R, ColorFormat>,
+               depth_target: &handle::DepthStencilView<R, DepthFormat>)
+               -> ArrowBrush<R, F>
+    {
+        ArrowBrush { lines: LineBrush::new(factory, target, depth_target, ARROW_DEPTH) }
+    }
+
+    pub fn draw<C>(&mut self,
+                   encoder: &mut Encoder<R, C>,
+                   transform: &UserViewTransform,
+                   state: &State,
+                   camera: &Camera) where C: CommandBuffer<R> {
+        let mut verts = Vec::new();
+        for (start, end, color) in body_arrows(state, camera) {
+            verts.push(LineVertex { position: start.cast().into(), color });
+            verts.push(LineVertex { position: end.cast().into(), color });
+        }
+        self.lines.draw_lines(encoder, transform, &verts);
+    }
+}
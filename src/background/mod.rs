@@ -2,5 +2,17 @@ pub mod render;
 
 use super::*;
 
+/// Procedural star field settings
 #[derive(Debug, Clone)]
-pub struct Background {}
+pub struct Background {
+    /// stars are laid out from this seed, the same seed always gives the same sky
+    pub seed: u32,
+    /// faint nebula noise intensity, 0 for none
+    pub nebula: f32,
+}
+
+impl Default for Background {
+    fn default() -> Background {
+        Background { seed: 1, nebula: 1.0 }
+    }
+}
@@ -1,4 +1,5 @@
-use super::{UserViewTransform, ColorFormat, DepthFormat};
+use super::{Background, ColorFormat, DepthFormat};
+use camera::Camera;
 use gfx::*;
 use gfx::traits::FactoryExt;
 use gfx;
@@ -9,31 +10,59 @@ pub struct BackgroundVertex {
     position: [f32; 2],
 }
 
+#[derive(ConstantBuffer, Debug, Clone, Copy)]
+pub struct StarfieldParams {
+    origin: [f32; 2],
+    zoom: f32,
+    aspect_ratio: f32,
+    rotation: f32,
+    screen_height: f32,
+    seed: u32,
+    nebula: f32,
+}
+
+impl StarfieldParams {
+    fn new(camera: &Camera, background: &Background) -> StarfieldParams {
+        StarfieldParams {
+            origin: camera.origin.into(),
+            zoom: camera.zoom,
+            aspect_ratio: camera.aspect_ratio(),
+            rotation: camera.rotation,
+            screen_height: camera.viewport.height as f32,
+            seed: background.seed,
+            nebula: background.nebula,
+        }
+    }
+}
+
 gfx_defines! {
     pipeline backgroundpipe {
         vbuf: VertexBuffer<BackgroundVertex> = (),
         out: RenderTarget<ColorFormat> = "out_color",
         out_depth: gfx::DepthTarget<DepthFormat> = gfx::preset::depth::LESS_EQUAL_WRITE,
-        global_transform: ConstantBuffer<UserViewTransform> = "global_transform",
+        starfield: ConstantBuffer<StarfieldParams> = "starfield",
     }
 }
 
+/// Fullscreen quad in clip space, the star field is worked out per pixel
 const BACKGROUND_QUAD: [BackgroundVertex; 4] = [
-    BackgroundVertex{ position: [-10000.0, 10000.0] },
-    BackgroundVertex{ position: [10000.0, 10000.0] },
-    BackgroundVertex{ position: [10000.0, -10000.0] },
-    BackgroundVertex{ position: [-10000.0, -10000.0] }];
+    BackgroundVertex{ position: [-1.0, 1.0] },
+    BackgroundVertex{ position: [1.0, 1.0] },
+    BackgroundVertex{ position: [1.0, -1.0] },
+    BackgroundVertex{ position: [-1.0, -1.0] }];
 
 pub struct BackgroundBrush<R: Resources, F: Factory<R>> {
     pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = backgroundpipe),
     slice: Slice<R>,
     data: backgroundpipe::Data<R>,
+    background: Background,
 }
 
 impl<R: Resources, F: Factory<R>> BackgroundBrush<R, F> {
     pub fn new(mut factory: F,
                   target: &handle::RenderTargetView<R, ColorFormat>,
-                  depth_target: &handle::DepthStencilView<R, DepthFormat>)
+                  depth_target: &handle::DepthStencilView<R, DepthFormat>,
+                  background: Background)
                   -> BackgroundBrush<R, F>
     {
         let (vertex_buffer, slice) = factory
@@ -42,7 +71,7 @@ impl<R: Resources, F: Factory<R>> BackgroundBrush<R, F> {
             vbuf: vertex_buffer,
             out: target.clone(),
             out_depth: depth_target.clone(),
-            global_transform: factory.create_constant_buffer(1),
+            starfield: factory.create_constant_buffer(1),
         };
 
         let pso_cell = debug_watcher_pso_cell!(
@@ -51,13 +80,13 @@ impl<R: Resources, F: Factory<R>> BackgroundBrush<R, F> {
             fragment_shader = "shader/frag.glsl",
             factory = factory).expect("BackgroundBrush pso");
 
-        BackgroundBrush { pso_cell, slice, data }
+        BackgroundBrush { pso_cell, slice, data, background }
     }
 
     pub fn draw<C>(&mut self,
                       encoder: &mut Encoder<R, C>,
-                      transform: &UserViewTransform) where C: CommandBuffer<R> {
-        encoder.update_constant_buffer(&self.data.global_transform, transform);
+                      camera: &Camera) where C: CommandBuffer<R> {
+        encoder.update_constant_buffer(&self.data.starfield, &StarfieldParams::new(camera, &self.background));
         encoder.draw(&self.slice, self.pso_cell.pso(), &self.data);
     }
 }
@@ -1,7 +1,110 @@
 #version 330 core
 
+layout(std140) uniform;
+
+uniform starfield {
+    vec2 origin;
+    float zoom;
+    float aspect_ratio;
+    float rotation;
+    float screen_height;
+    uint seed;
+    float nebula;
+};
+
+in vec2 screen_pos;
+
 out vec4 out_color;
 
+const vec3 space_color = vec3(0.0, 0.06, 0.08);
+const int layers = 3;
+// parallax depth of each layer, 1 would move exactly with the world
+const float layer_depths[layers] = float[](0.12, 0.3, 0.55);
+// layers zoom from this reference towards the camera zoom by their depth
+const float reference_zoom = 16.0;
+const float cell_size = 2.0;
+const float star_chance = 0.35;
+
+uint hash(uint x) {
+    x ^= x >> 16;
+    x *= 0x7feb352dU;
+    x ^= x >> 15;
+    x *= 0x846ca68bU;
+    x ^= x >> 16;
+    return x;
+}
+
+/// Stable random value in [0, 1] for a cell, layer & value index
+float random(ivec2 cell, int layer, int n) {
+    uint h = hash(uint(cell.x) ^ hash(uint(cell.y) ^ hash(seed ^ hash(uint(layer * 8 + n)))));
+    return float(h) / 4294967295.0;
+}
+
+vec2 rotate(vec2 v, float angle) {
+    float s = sin(angle);
+    float c = cos(angle);
+    return vec2(c * v.x - s * v.y, s * v.x + c * v.y);
+}
+
+/// Layer position of this pixel, far layers barely move with panning or zooming
+vec2 layer_position(vec2 screen, float depth, out float pixel) {
+    float layer_zoom = exp(mix(log(reference_zoom), log(zoom), depth));
+    pixel = 2.0 * layer_zoom / screen_height;
+    return origin * depth + screen * layer_zoom;
+}
+
+/// At most one star per cell, a pixel or two across at any zoom
+vec3 stars(vec2 screen, int layer) {
+    float depth = layer_depths[layer];
+    float pixel;
+    vec2 pos = layer_position(screen, depth, pixel);
+    ivec2 cell = ivec2(floor(pos / cell_size));
+    if (random(cell, layer, 0) > star_chance) {
+        return vec3(0.0);
+    }
+    // keep stars away from cell edges so neighbours needn't be checked
+    vec2 offset = vec2(mix(0.1, 0.9, random(cell, layer, 1)), mix(0.1, 0.9, random(cell, layer, 2)));
+    vec2 star = (vec2(cell) + offset) * cell_size;
+    float radius = pixel * mix(0.6, 1.5, random(cell, layer, 3));
+    float brightness = mix(0.25, 1.0, random(cell, layer, 4)) * mix(0.5, 1.0, depth);
+    // slightly blue or yellow white
+    vec3 tint = mix(vec3(0.75, 0.85, 1.0), vec3(1.0, 0.9, 0.75), random(cell, layer, 5));
+    return tint * brightness * (1.0 - smoothstep(radius * 0.5, radius * 1.5, distance(pos, star)));
+}
+
+float value_noise(vec2 pos) {
+    ivec2 cell = ivec2(floor(pos));
+    vec2 f = smoothstep(0.0, 1.0, fract(pos));
+    float a = random(cell, layers, 0);
+    float b = random(cell + ivec2(1, 0), layers, 0);
+    float c = random(cell + ivec2(0, 1), layers, 0);
+    float d = random(cell + ivec2(1, 1), layers, 0);
+    return mix(mix(a, b, f.x), mix(c, d, f.x), f.y);
+}
+
+/// Faint coloured clouds moving with the furthest layer
+vec3 nebula_clouds(vec2 screen) {
+    float pixel;
+    vec2 pos = layer_position(screen, layer_depths[0] * 0.5, pixel) / 40.0;
+    float noise = 0.0;
+    float amplitude = 0.5;
+    for (int octave = 0; octave < 4; octave++) {
+        noise += amplitude * value_noise(pos);
+        pos *= 2.0;
+        amplitude *= 0.5;
+    }
+    vec3 tint = mix(vec3(0.3, 0.1, 0.4), vec3(0.05, 0.2, 0.35), value_noise(pos * 0.05));
+    return tint * pow(noise, 3.0) * 0.12;
+}
+
 void main() {
-    out_color = vec4(0.0, 0.06, 0.08, 0.0);
+    vec2 screen = rotate(screen_pos * vec2(aspect_ratio, 1.0), rotation);
+    vec3 color = space_color;
+    if (nebula > 0.0) {
+        color += nebula * nebula_clouds(screen);
+    }
+    for (int layer = 0; layer < layers; layer++) {
+        color += stars(screen, layer);
+    }
+    out_color = vec4(color, 0.0);
 }
@@ -1,14 +1,11 @@
 #version 330 core
 
-layout(std140) uniform;
-
-uniform global_transform {
-    mat4 view;
-    mat4 proj;
-};
-
 in vec2 position;
 
+out vec2 screen_pos;
+
 void main() {
-    gl_Position =  proj * view * vec4(position, 1.0, 1.0);
+    screen_pos = position;
+    // furthest depth, behind everything
+    gl_Position = vec4(position, 1.0, 1.0);
 }
@@ -0,0 +1,186 @@
+//! Headless batch simulation, runs a scenario file for a simulated duration
+//! writing body trajectories to stdout (or `--output`) & a final state summary to stderr.
+//!
+//! `cargo run --bin orbit-batch -- scenario.txt --duration 10 --step 0.001 --every 100 --format csv`
+extern crate prototype_orbit;
+extern crate cgmath;
+
+use cgmath::*;
+use prototype_orbit::clock::Clock;
+use prototype_orbit::compute::{compute_state, GRAVITY};
+use prototype_orbit::input::Tasks;
+use prototype_orbit::scenario;
+use prototype_orbit::state::State;
+use prototype_orbit::OrbitBody;
+use std::env;
+use std::fs::File;
+use std::io::{self, Write, BufWriter};
+use std::process;
+
+const USAGE: &str = "Usage: orbit-batch SCENARIO --duration SECONDS [--step SECONDS] \
+                     [--every STEPS] [--format csv|jsonl] [--output FILE]";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv,
+    JsonLines,
+}
+
+#[derive(Debug)]
+struct Args {
+    scenario: String,
+    duration: f64,
+    step: f64,
+    every: u64,
+    format: Format,
+    output: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let mut scenario = None;
+    let mut duration = None;
+    let mut step = 0.001;
+    let mut every = 1;
+    let mut format = Format::Csv;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}", name));
+        match arg.as_str() {
+            "--duration" => duration = Some(parse_positive(&value("--duration")?)?),
+            "--step" => step = parse_positive(&value("--step")?)?,
+            "--every" => {
+                every = value("--every")?.parse()
+                    .map_err(|_| "--every expects a whole number of steps".to_string())?;
+                if every == 0 {
+                    return Err("--every must be at least 1".into());
+                }
+            }
+            "--format" => format = match value("--format")?.as_str() {
+                "csv" => Format::Csv,
+                "jsonl" | "json" => Format::JsonLines,
+                other => return Err(format!("Unknown format `{}`", other)),
+            },
+            "--output" | "-o" => output = Some(value("--output")?),
+            "--help" | "-h" => return Err(USAGE.into()),
+            _ if scenario.is_none() && !arg.starts_with('-') => scenario = Some(arg),
+            _ => return Err(format!("Unexpected argument `{}`", arg)),
+        }
+    }
+
+    Ok(Args {
+        scenario: scenario.ok_or("Missing SCENARIO file")?,
+        duration: duration.ok_or("Missing --duration")?,
+        step,
+        every,
+        format,
+        output,
+    })
+}
+
+fn parse_positive(val: &str) -> Result<f64, String> {
+    match val.parse::<f64>() {
+        Ok(v) if v.is_finite() && v > 0.0 => Ok(v),
+        _ => Err(format!("Expected a positive finite number, got `{}`", val)),
+    }
+}
+
+fn write_header<W: Write>(out: &mut W, format: Format) -> io::Result<()> {
+    match format {
+        Format::Csv => writeln!(out, "time,id,x,y,vx,vy"),
+        Format::JsonLines => Ok(()),
+    }
+}
+
+fn write_bodies<W: Write>(out: &mut W, format: Format, time: f64, bodies: &[OrbitBody]) -> io::Result<()> {
+    for (id, body) in bodies.iter().enumerate() {
+        let (c, v) = (body.center, body.velocity);
+        match format {
+            Format::Csv => writeln!(out, "{},{},{},{},{},{}", time, id, c.x, c.y, v.x, v.y)?,
+            Format::JsonLines => writeln!(out,
+                r#"{{"time":{},"id":{},"x":{},"y":{},"vx":{},"vy":{}}}"#,
+                time, id, c.x, c.y, v.x, v.y)?,
+        }
+    }
+    Ok(())
+}
+
+/// Total kinetic + gravitational potential energy, should be ~conserved
+fn total_energy(bodies: &[OrbitBody]) -> f64 {
+    let mut energy = 0.0;
+    for (idx, body) in bodies.iter().enumerate() {
+        energy += 0.5 * body.mass * body.velocity.magnitude2();
+        for other in &bodies[idx+1..] {
+            energy -= GRAVITY * body.mass * other.mass / body.center.distance(other.center);
+        }
+    }
+    energy
+}
+
+fn total_momentum(bodies: &[OrbitBody]) -> Vector2<f64> {
+    bodies.iter().fold(Vector2::zero(), |sum, body| sum + body.velocity * body.mass)
+}
+
+fn write_summary<W: Write>(out: &mut W, initial: &[OrbitBody], bodies: &[OrbitBody], time: f64, steps: u64)
+    -> io::Result<()>
+{
+    writeln!(out, "Simulated {}s in {} steps", time, steps)?;
+    for (id, (body, start)) in bodies.iter().zip(initial).enumerate() {
+        let name = body.name.as_ref().map(|name| format!(" ({})", name)).unwrap_or_default();
+        writeln!(out, "body {}{}: position ({:.6}, {:.6}) velocity ({:.6}, {:.6}) displacement {:.6}",
+                 id, name, body.center.x, body.center.y, body.velocity.x, body.velocity.y,
+                 body.center.distance(start.center))?;
+    }
+    let (energy, initial_energy) = (total_energy(bodies), total_energy(initial));
+    writeln!(out, "energy {:.9} (initial {:.9}, drift {:.3e})",
+             energy, initial_energy, (energy - initial_energy) / initial_energy.abs())?;
+    let momentum = total_momentum(bodies);
+    writeln!(out, "momentum ({:.9}, {:.9})", momentum.x, momentum.y)
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let bodies = scenario::load(&args.scenario).map_err(|err| format!("{}: {}", args.scenario, err))?;
+
+    match args.output {
+        Some(ref path) => {
+            let file = File::create(path).map_err(|err| format!("Could not create {}: {}", path, err))?;
+            simulate(&args, bodies, BufWriter::new(file))
+        }
+        None => {
+            let stdout = io::stdout();
+            let out = BufWriter::new(stdout.lock());
+            simulate(&args, bodies, out)
+        }
+    }.map_err(|err| format!("Write failed: {}", err))
+}
+
+fn simulate<W: Write>(args: &Args, bodies: Vec<OrbitBody>, mut out: W) -> io::Result<()> {
+    let mut state = State::new(1, 1);
+    state.drawables.orbit_bodies = bodies.clone();
+    // headless, so time is simulated only
+    let mut tasks = Tasks::with_clock(Clock::manual());
+
+    let steps = (args.duration / args.step).round() as u64;
+
+    write_header(&mut out, args.format)?;
+    write_bodies(&mut out, args.format, 0.0, &state.drawables.orbit_bodies)?;
+    for step in 1..(steps + 1) {
+        tasks.clock.advance(args.step);
+        compute_state(&mut state, &mut tasks, args.step);
+        if step % args.every == 0 || step == steps {
+            write_bodies(&mut out, args.format, step as f64 * args.step, &state.drawables.orbit_bodies)?;
+        }
+    }
+    out.flush()?;
+
+    write_summary(&mut io::stderr(), &bodies, &state.drawables.orbit_bodies, steps as f64 * args.step, steps)
+}
+
+fn main() {
+    let result = parse_args().and_then(run);
+    if let Err(err) = result {
+        let _ = writeln!(io::stderr(), "{}", err);
+        process::exit(1);
+    }
+}
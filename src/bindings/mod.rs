@@ -0,0 +1,535 @@
+//! Action based input bindings, with defaults that can be overridden by a config file
+//!
+//! Each config line binds an action to a comma separated list of inputs, replacing the
+//! default inputs for that action. Blank lines & anything after a `#` are ignored
+//! ```text
+//! pause = Space
+//! follow_previous = Shift+Tab, Back
+//! follow_nearest = MouseMiddle, N
+//! zoom_in = WheelUp, PageUp
+//! double_click_ms = 400
+//! ```
+pub mod render;
+
+use glutin::*;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+pub const BINDINGS_FILE: &str = "bindings.conf";
+const DEFAULT_DBL_CLICK_MS: u64 = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    Pause,
+    ToggleCurves,
+    /// show or hide past paths
+    ToggleTrails,
+    /// show or hide the grid, scale bar & cursor coordinate
+    ToggleGrid,
+    /// cycle the gravitational field overlay through potential, acceleration & arrows
+    CycleField,
+    /// show or hide field overlay contour lines
+    ToggleContours,
+    /// show or hide velocity & acceleration arrows
+    ToggleArrows,
+    /// switch arrows between linear & log length
+    ToggleArrowScale,
+    /// show or hide body name labels
+    ToggleLabels,
+    /// show or hide the system overview inset
+    ToggleMinimap,
+    /// show or hide the glow around luminous bodies
+    ToggleBloom,
+    ShowBindings,
+    /// show or hide the detailed debug HUD & timing graph
+    ToggleDebug,
+    FollowHeaviest,
+    /// follow the body at this index
+    Follow(u8),
+    FollowNext,
+    FollowPrevious,
+    FollowNearest,
+    ToggleFollowOrder,
+    AutoFrame,
+    AutoFrameVisible,
+    CoRotate,
+    /// split the window between 1, 2 or 4 cameras
+    CycleSplit,
+    ZoomIn,
+    ZoomOut,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    /// left-drag panning & double-click following
+    Grab,
+    /// play or pause the loaded camera path
+    PlayPath,
+    StopPath,
+}
+
+impl Action {
+    pub fn all() -> Vec<Action> {
+        let mut actions = vec!(Action::Quit, Action::Pause, Action::ToggleCurves, Action::ToggleTrails,
+                               Action::ToggleGrid, Action::CycleField, Action::ToggleContours,
+                               Action::ToggleArrows, Action::ToggleArrowScale, Action::ToggleLabels,
+                               Action::ToggleMinimap, Action::ToggleBloom, Action::ShowBindings, Action::ToggleDebug,
+                               Action::FollowHeaviest);
+        actions.extend((0..10).map(Action::Follow));
+        actions.extend(vec!(Action::FollowNext, Action::FollowPrevious, Action::FollowNearest,
+                            Action::ToggleFollowOrder, Action::AutoFrame, Action::AutoFrameVisible,
+                            Action::CoRotate, Action::CycleSplit, Action::ZoomIn, Action::ZoomOut, Action::PanUp,
+                            Action::PanDown, Action::PanLeft, Action::PanRight, Action::Grab,
+                            Action::PlayPath, Action::StopPath));
+        actions
+    }
+
+    /// Config file name, ie `toggle_curves`
+    pub fn name(&self) -> String {
+        match *self {
+            Action::Quit => "quit".into(),
+            Action::Pause => "pause".into(),
+            Action::ToggleCurves => "toggle_curves".into(),
+            Action::ToggleTrails => "toggle_trails".into(),
+            Action::ToggleGrid => "toggle_grid".into(),
+            Action::CycleField => "cycle_field".into(),
+            Action::ToggleContours => "toggle_contours".into(),
+            Action::ToggleArrows => "toggle_arrows".into(),
+            Action::ToggleArrowScale => "toggle_arrow_scale".into(),
+            Action::ToggleLabels => "toggle_labels".into(),
+            Action::ToggleMinimap => "toggle_minimap".into(),
+            Action::ToggleBloom => "toggle_bloom".into(),
+            Action::ShowBindings => "show_bindings".into(),
+            Action::ToggleDebug => "toggle_debug".into(),
+            Action::FollowHeaviest => "follow_heaviest".into(),
+            Action::Follow(idx) => format!("follow_{}", idx + 1),
+            Action::FollowNext => "follow_next".into(),
+            Action::FollowPrevious => "follow_previous".into(),
+            Action::FollowNearest => "follow_nearest".into(),
+            Action::ToggleFollowOrder => "toggle_follow_order".into(),
+            Action::AutoFrame => "auto_frame".into(),
+            Action::AutoFrameVisible => "auto_frame_visible".into(),
+            Action::CoRotate => "co_rotate".into(),
+            Action::CycleSplit => "cycle_split".into(),
+            Action::ZoomIn => "zoom_in".into(),
+            Action::ZoomOut => "zoom_out".into(),
+            Action::PanUp => "pan_up".into(),
+            Action::PanDown => "pan_down".into(),
+            Action::PanLeft => "pan_left".into(),
+            Action::PanRight => "pan_right".into(),
+            Action::Grab => "grab".into(),
+            Action::PlayPath => "play_path".into(),
+            Action::StopPath => "stop_path".into(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::all().into_iter().find(|action| action.name() == name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trigger {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    WheelUp,
+    WheelDown,
+}
+
+impl Trigger {
+    /// Returns the key, mouse button or wheel direction of an input event
+    pub fn of(event: &WindowEvent) -> Option<Trigger> {
+        match *event {
+            WindowEvent::KeyboardInput(_, _, Some(key), _) => Some(Trigger::Key(key)),
+            WindowEvent::MouseInput(_, button) => Some(Trigger::Mouse(button)),
+            WindowEvent::MouseWheel(MouseScrollDelta::LineDelta(_, y), ..) |
+            WindowEvent::MouseWheel(MouseScrollDelta::PixelDelta(_, y), ..) => {
+                if y > 0.0 { Some(Trigger::WheelUp) }
+                else if y < 0.0 { Some(Trigger::WheelDown) }
+                else { None }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Input {
+    pub trigger: Trigger,
+    pub shift: bool,
+}
+
+impl Input {
+    pub fn key(key: VirtualKeyCode) -> Input {
+        Input { trigger: Trigger::Key(key), shift: false }
+    }
+
+    pub fn shift_key(key: VirtualKeyCode) -> Input {
+        Input { trigger: Trigger::Key(key), shift: true }
+    }
+
+    pub fn mouse(button: MouseButton) -> Input {
+        Input { trigger: Trigger::Mouse(button), shift: false }
+    }
+
+    pub fn wheel(trigger: Trigger) -> Input {
+        Input { trigger, shift: false }
+    }
+
+    /// Parses config names like `Tab`, `Shift+Tab`, `MouseLeft`, `Mouse4`, `WheelUp`
+    pub fn from_name(name: &str) -> Option<Input> {
+        let (shift, name) = match name.trim() {
+            n if n.starts_with("Shift+") => (true, &n["Shift+".len()..]),
+            n => (false, n),
+        };
+        let trigger = match name {
+            "MouseLeft" => Trigger::Mouse(MouseButton::Left),
+            "MouseRight" => Trigger::Mouse(MouseButton::Right),
+            "MouseMiddle" => Trigger::Mouse(MouseButton::Middle),
+            "WheelUp" => Trigger::WheelUp,
+            "WheelDown" => Trigger::WheelDown,
+            n if n.starts_with("Mouse") => Trigger::Mouse(MouseButton::Other(n["Mouse".len()..].parse().ok()?)),
+            _ => Trigger::Key(key_from_name(name)?),
+        };
+        Some(Input { trigger, shift })
+    }
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        match self.trigger {
+            Trigger::Key(key) => write!(f, "{:?}", key),
+            Trigger::Mouse(MouseButton::Other(button)) => write!(f, "Mouse{}", button),
+            Trigger::Mouse(button) => write!(f, "Mouse{:?}", button),
+            Trigger::WheelUp => write!(f, "WheelUp"),
+            Trigger::WheelDown => write!(f, "WheelDown"),
+        }
+    }
+}
+
+macro_rules! key_from_name {
+    ($($key:ident),*) => {
+        fn key_from_name(name: &str) -> Option<VirtualKeyCode> {
+            match name {
+                $(stringify!($key) => Some(VirtualKeyCode::$key),)*
+                _ => None,
+            }
+        }
+    }
+}
+
+key_from_name!(
+    Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0,
+    A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+    Escape, F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+    Insert, Home, Delete, End, PageDown, PageUp, Left, Up, Right, Down,
+    Back, Return, Space, Tab,
+    Numpad0, Numpad1, Numpad2, Numpad3, Numpad4, Numpad5, Numpad6, Numpad7, Numpad8, Numpad9,
+    Add, Subtract, Multiply, Divide, Decimal, NumpadEnter, NumpadEquals,
+    Apostrophe, Backslash, Comma, Equals, Grave, LBracket, Minus, Period, RBracket, Semicolon, Slash);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingsError {
+    /// 1-based line number the error occurred on, 0 if not line specific
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.reason)
+        }
+        else {
+            write!(f, "line {}: {}", self.line, self.reason)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InputMap {
+    bindings: Vec<(Action, Input)>,
+    pub double_click_ms: u64,
+}
+
+impl Default for InputMap {
+    fn default() -> InputMap {
+        use glutin::VirtualKeyCode::*;
+
+        let mut bindings = vec!(
+            (Action::Quit, Input::key(Escape)),
+            (Action::Pause, Input::key(P)),
+            (Action::ToggleCurves, Input::key(C)),
+            (Action::ToggleTrails, Input::shift_key(C)),
+            (Action::ToggleGrid, Input::key(G)),
+            (Action::CycleField, Input::key(H)),
+            (Action::ToggleContours, Input::shift_key(H)),
+            (Action::ToggleArrows, Input::key(K)),
+            (Action::ToggleArrowScale, Input::shift_key(K)),
+            (Action::ToggleLabels, Input::key(L)),
+            (Action::ToggleMinimap, Input::key(M)),
+            (Action::ToggleBloom, Input::key(B)),
+            (Action::ShowBindings, Input::key(F1)),
+            (Action::ToggleDebug, Input::key(F3)),
+            (Action::FollowHeaviest, Input::key(Home)));
+        let number_keys = [Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9, Key0];
+        for (idx, key) in number_keys.iter().enumerate() {
+            bindings.push((Action::Follow(idx as u8), Input::key(*key)));
+        }
+        bindings.extend(vec!(
+            (Action::FollowNext, Input::key(Tab)),
+            (Action::FollowPrevious, Input::shift_key(Tab)),
+            (Action::FollowNearest, Input::mouse(MouseButton::Middle)),
+            (Action::ToggleFollowOrder, Input::key(O)),
+            (Action::AutoFrame, Input::key(F)),
+            (Action::AutoFrameVisible, Input::shift_key(F)),
+            (Action::CoRotate, Input::key(R)),
+            (Action::CycleSplit, Input::key(X)),
+            (Action::ZoomIn, Input::wheel(Trigger::WheelUp)),
+            (Action::ZoomIn, Input::key(Add)),
+            (Action::ZoomIn, Input::key(Equals)),
+            (Action::ZoomIn, Input::key(PageUp)),
+            (Action::ZoomOut, Input::wheel(Trigger::WheelDown)),
+            (Action::ZoomOut, Input::key(Subtract)),
+            (Action::ZoomOut, Input::key(Minus)),
+            (Action::ZoomOut, Input::key(PageDown)),
+            (Action::PanUp, Input::key(Up)),
+            (Action::PanUp, Input::key(W)),
+            (Action::PanDown, Input::key(Down)),
+            (Action::PanDown, Input::key(S)),
+            (Action::PanLeft, Input::key(Left)),
+            (Action::PanLeft, Input::key(A)),
+            (Action::PanRight, Input::key(Right)),
+            (Action::PanRight, Input::key(D)),
+            (Action::Grab, Input::mouse(MouseButton::Left)),
+            (Action::PlayPath, Input::key(V)),
+            (Action::StopPath, Input::shift_key(V))));
+
+        InputMap { bindings, double_click_ms: DEFAULT_DBL_CLICK_MS }
+    }
+}
+
+impl InputMap {
+    /// Loads bindings from a config file, using defaults if the file doesn't exist
+    /// or is invalid
+    pub fn load_or_default<P: AsRef<Path>>(path: P) -> InputMap {
+        let mut config = String::new();
+        match File::open(path.as_ref()).and_then(|mut file| file.read_to_string(&mut config)) {
+            Ok(_) => {},
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return InputMap::default(),
+            Err(err) => {
+                warn!("Could not read {}, using default bindings: {}", path.as_ref().display(), err);
+                return InputMap::default();
+            }
+        }
+        match InputMap::parse(&config) {
+            Ok(bindings) => {
+                info!("Loaded input bindings from {}", path.as_ref().display());
+                bindings
+            }
+            Err(err) => {
+                warn!("Invalid {}, using default bindings: {}", path.as_ref().display(), err);
+                InputMap::default()
+            }
+        }
+    }
+
+    /// Parses config overriding the defaults. Inputs configured for an action take
+    /// precedence over conflicting defaults, conflicts between configured actions are errors.
+    pub fn parse(config: &str) -> Result<InputMap, BindingsError> {
+        let mut map = InputMap::default();
+        let mut configured: Vec<(Action, usize)> = Vec::new();
+
+        for (idx, line) in config.lines().enumerate() {
+            let line_number = idx + 1;
+            let err = |reason: String| BindingsError { line: line_number, reason };
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name.trim(), value.trim()),
+                _ => return Err(err("Expected `action = input, ...`".into())),
+            };
+
+            if name == "double_click_ms" {
+                map.double_click_ms = value.parse()
+                    .map_err(|_| err(format!("Invalid double_click_ms `{}`", value)))?;
+                continue;
+            }
+
+            let action = Action::from_name(name).ok_or_else(|| err(format!("Unknown action `{}`", name)))?;
+            let inputs = value.split(',')
+                .map(|input| Input::from_name(input).ok_or_else(|| err(format!("Unknown input `{}`", input.trim()))))
+                .collect::<Result<Vec<_>, _>>()?;
+            for input in &inputs {
+                match input.trigger {
+                    Trigger::Key(_) => {},
+                    // only keyboard events carry modifiers
+                    _ if input.shift => return Err(err(format!("Shift only applies to keys, got `{}`", input))),
+                    _ => {},
+                }
+            }
+
+            map.bindings.retain(|&(a, _)| a != action);
+            for input in inputs {
+                if let Some(&(other, other_line)) = configured.iter()
+                    .find(|&&(a, _)| a != action && map.action(input) == Some(a)) {
+                    return Err(err(format!("{} is already bound to {} on line {}",
+                                           input, other.name(), other_line)));
+                }
+                // configured inputs replace conflicting defaults
+                if let Some(default_action) = map.action(input) {
+                    debug!("{} replaces default {} binding {}", action.name(), default_action.name(), input);
+                }
+                map.bindings.retain(|&(_, i)| i != input);
+                map.bindings.push((action, input));
+            }
+            configured.push((action, line_number));
+        }
+        Ok(map)
+    }
+
+    /// Returns the action bound to the exact input
+    pub fn action(&self, input: Input) -> Option<Action> {
+        self.bindings.iter().find(|&&(_, i)| i == input).map(|&(a, _)| a)
+    }
+
+    /// Returns the action for a key or mouse button event & whether it was pressed.
+    /// Shift+key uses the plain key binding if it has none of its own.
+    pub fn event_action(&self, event: &WindowEvent) -> Option<(Action, bool)> {
+        let (shift, element_state) = match *event {
+            WindowEvent::KeyboardInput(element_state, _, _, modifiers) => (modifiers.shift, element_state),
+            WindowEvent::MouseInput(element_state, _) => (false, element_state),
+            _ => return None,
+        };
+        let input = Input { trigger: Trigger::of(event)?, shift };
+        let action = self.action(input)
+            .or_else(|| if input.shift { self.action(Input { shift: false, ..input }) } else { None })?;
+        Some((action, element_state == ElementState::Pressed))
+    }
+
+    /// Returns the action for a mouse wheel event, wheel events have no press or release
+    pub fn wheel_action(&self, event: &WindowEvent) -> Option<Action> {
+        match *event {
+            WindowEvent::MouseWheel(..) => self.action(Input { trigger: Trigger::of(event)?, shift: false }),
+            _ => None,
+        }
+    }
+
+    pub fn inputs(&self, action: Action) -> Vec<Input> {
+        self.bindings.iter().filter(|&&(a, _)| a == action).map(|&(_, i)| i).collect()
+    }
+
+    /// Returns (action name, bound inputs) lines for display
+    pub fn listing(&self) -> Vec<(String, String)> {
+        Action::all().into_iter()
+            .map(|action| {
+                let inputs: Vec<String> = self.inputs(action).iter().map(|i| i.to_string()).collect();
+                (action.name(), if inputs.is_empty() { "-".into() } else { inputs.join(", ") })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod bindings_test {
+    use super::*;
+
+    #[test]
+    fn default_bindings_have_no_conflicts() {
+        let map = InputMap::default();
+        for &(action, input) in &map.bindings {
+            assert_eq!(map.action(input), Some(action), "{} conflicts", input);
+        }
+        assert_eq!(map.action(Input::key(VirtualKeyCode::Key3)), Some(Action::Follow(2)));
+        assert_eq!(map.double_click_ms, DEFAULT_DBL_CLICK_MS);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for action in Action::all() {
+            assert_eq!(Action::from_name(&action.name()), Some(action));
+        }
+        for &(_, input) in &InputMap::default().bindings {
+            assert_eq!(Input::from_name(&input.to_string()), Some(input));
+        }
+        let other = Input::mouse(MouseButton::Other(4));
+        assert_eq!(other.to_string(), "Mouse4");
+        assert_eq!(Input::from_name("Mouse4"), Some(other));
+        assert_eq!(Input::from_name("Mouse256"), None);
+    }
+
+    #[test]
+    fn parse_overrides_defaults() {
+        let map = InputMap::parse("
+            # custom
+            pause = Space, MouseRight
+            follow_nearest = N # keyboard instead
+            double_click_ms = 300
+        ").unwrap();
+
+        assert_eq!(map.action(Input::key(VirtualKeyCode::Space)), Some(Action::Pause));
+        assert_eq!(map.action(Input::mouse(MouseButton::Right)), Some(Action::Pause));
+        assert_eq!(map.action(Input::key(VirtualKeyCode::P)), None);
+        assert_eq!(map.inputs(Action::FollowNearest), vec!(Input::key(VirtualKeyCode::N)));
+        assert_eq!(map.inputs(Action::ToggleCurves), vec!(Input::key(VirtualKeyCode::C)));
+        assert_eq!(map.double_click_ms, 300);
+    }
+
+    #[test]
+    fn configured_bindings_replace_conflicting_defaults() {
+        let map = InputMap::parse("pause = C").unwrap();
+        assert_eq!(map.action(Input::key(VirtualKeyCode::C)), Some(Action::Pause));
+        assert!(map.inputs(Action::ToggleCurves).is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(InputMap::parse("pause = C\ntoggle_curves = C").unwrap_err(), BindingsError {
+            line: 2,
+            reason: "C is already bound to pause on line 1".into(),
+        });
+        assert_eq!(InputMap::parse("\n\nexplode = X").unwrap_err().line, 3);
+        assert_eq!(InputMap::parse("pause = Ctrl+P").unwrap_err().reason, "Unknown input `Ctrl+P`");
+        assert_eq!(InputMap::parse("pause").unwrap_err().line, 1);
+        assert_eq!(InputMap::parse("grab = Shift+MouseLeft").unwrap_err().reason,
+                   "Shift only applies to keys, got `Shift+MouseLeft`");
+        assert_eq!(InputMap::parse("zoom_in = Shift+WheelUp").unwrap_err().line, 1);
+    }
+
+    fn shift_key_event(element_state: ElementState, key: VirtualKeyCode) -> WindowEvent {
+        let mut event = WindowEvent::KeyboardInput(element_state, 0, Some(key), Default::default());
+        if let WindowEvent::KeyboardInput(_, _, _, ref mut modifiers) = event {
+            modifiers.shift = true;
+        }
+        event
+    }
+
+    #[test]
+    fn shift_falls_back_to_plain_key() {
+        let map = InputMap::default();
+        let shift_tab = shift_key_event(ElementState::Pressed, VirtualKeyCode::Tab);
+        assert_eq!(map.event_action(&shift_tab), Some((Action::FollowPrevious, true)));
+
+        let shift_d = shift_key_event(ElementState::Released, VirtualKeyCode::D);
+        assert_eq!(map.event_action(&shift_d), Some((Action::PanRight, false)));
+    }
+
+    #[test]
+    fn wheel_bindings() {
+        let map = InputMap::parse("zoom_in = WheelDown\nzoom_out = WheelUp").unwrap();
+        let scroll = |y| WindowEvent::MouseWheel(MouseScrollDelta::PixelDelta(0.0, y), TouchPhase::Moved);
+        assert_eq!(map.wheel_action(&scroll(-3.0)), Some(Action::ZoomIn));
+        assert_eq!(map.wheel_action(&scroll(3.0)), Some(Action::ZoomOut));
+        assert_eq!(map.wheel_action(&scroll(0.0)), None);
+        assert_eq!(map.event_action(&scroll(3.0)), None);
+    }
+}
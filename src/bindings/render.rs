@@ -0,0 +1,36 @@
+use super::InputMap;
+use gfx::*;
+use gfx_text::{HorizontalAnchor, VerticalAnchor};
+use gfx_text;
+
+const LINE_HEIGHT: i32 = 16;
+
+pub struct BindingsBrush<R: Resources, F: Factory<R>> {
+    renderer: gfx_text::Renderer<R, F>,
+    lines: Vec<(String, String)>,
+}
+
+impl<R: Resources, F: Factory<R> + Clone> BindingsBrush<R, F> {
+    pub fn new(factory: &F, bindings: &InputMap) -> BindingsBrush<R, F> {
+        let renderer = gfx_text::new(factory.clone()).with_size(13).unwrap();
+        BindingsBrush { renderer, lines: bindings.listing() }
+    }
+
+    /// Draws the binding listing anchored to the top-right of the screen
+    pub fn draw<C: CommandBuffer<R>, T: format::RenderFormat>(&mut self, encoder: &mut Encoder<R, C>, target: &handle::RenderTargetView<R, T>, screen_width: u32)
+                      -> Result<(), gfx_text::Error>
+    {
+        let right = screen_width as i32 - 5;
+        for (idx, line) in self.lines.iter().enumerate() {
+            let (ref action, ref inputs) = *line;
+            let y = 5 + idx as i32 * LINE_HEIGHT;
+            self.renderer.add_anchored(action, [right - 160, y],
+                           HorizontalAnchor::Right, VerticalAnchor::Top,
+                           [0.3, 0.6, 0.8, 1.0]);
+            self.renderer.add_anchored(inputs, [right - 150, y],
+                           HorizontalAnchor::Left, VerticalAnchor::Top,
+                           [0.8, 0.8, 0.8, 1.0]);
+        }
+        self.renderer.draw(encoder, target)
+    }
+}
@@ -0,0 +1,27 @@
+//! Bloom post-process: light in the high dynamic range frame beyond the displayable range is
+//! picked out by a bright pass, blurred & added back as the frame is copied to the window
+pub mod render;
+
+/// Blur iterations over the bright pass, each spreading the glow further
+pub const BLUR_PASSES: usize = 3;
+/// Colour channels brighter than this bloom, by the amount they exceed it
+pub const BRIGHT_THRESHOLD: f32 = 1.0;
+
+/// (horizontal, vertical) texture coordinate distance between the samples of a blur pass,
+/// later passes sample further apart to widen the glow
+pub fn blur_steps(texel_size: [f32; 2], pass: usize) -> ([f32; 2], [f32; 2]) {
+    let spread = (pass + 1) as f32;
+    ([texel_size[0] * spread, 0.0], [0.0, texel_size[1] * spread])
+}
+
+#[cfg(test)]
+mod bloom_test {
+    use super::*;
+
+    #[test]
+    fn blur_passes_spread() {
+        let texel_size = [0.01, 0.02];
+        assert_eq!(blur_steps(texel_size, 0), ([0.01, 0.0], [0.0, 0.02]));
+        assert_eq!(blur_steps(texel_size, 2), ([0.03, 0.0], [0.0, 0.06]));
+    }
+}
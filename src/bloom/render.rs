@@ -0,0 +1,169 @@
+use super::*;
+use super::super::WindowFormat;
+use gfx::*;
+use gfx::traits::FactoryExt;
+use gfx;
+use gfx_shader_watch::*;
+
+/// Glow targets hold light beyond the displayable range, so bright overlaps keep building
+pub type BloomFormat = gfx::format::Rgba16F;
+
+#[derive(VertexData, Debug, Clone, Copy)]
+pub struct BloomVertex {
+    position: [f32; 2],
+}
+
+gfx_defines! {
+    // texture coordinate distance between blur samples
+    constant BlurParams {
+        texel_step: [f32; 2] = "texel_step",
+    }
+
+    constant BrightParams {
+        threshold: f32 = "threshold",
+    }
+
+    // strength 1 adds the glow, 0 copies the frame as is
+    constant CompositeParams {
+        strength: f32 = "strength",
+    }
+
+    pipeline brightpipe {
+        vbuf: VertexBuffer<BloomVertex> = (),
+        params: ConstantBuffer<BrightParams> = "bright_params",
+        scene: TextureSampler<[f32; 4]> = "scene",
+        out: RenderTarget<BloomFormat> = "out_color",
+    }
+
+    pipeline blurpipe {
+        vbuf: VertexBuffer<BloomVertex> = (),
+        params: ConstantBuffer<BlurParams> = "blur_params",
+        source: TextureSampler<[f32; 4]> = "source",
+        out: RenderTarget<BloomFormat> = "out_color",
+    }
+
+    pipeline compositepipe {
+        vbuf: VertexBuffer<BloomVertex> = (),
+        params: ConstantBuffer<CompositeParams> = "composite_params",
+        scene: TextureSampler<[f32; 4]> = "scene",
+        glow: TextureSampler<[f32; 4]> = "glow",
+        out: RenderTarget<WindowFormat> = "out_color",
+    }
+}
+
+/// Square covering the whole target in clip space
+const BLOOM_QUAD: [BloomVertex; 4] = [
+    BloomVertex{ position: [-1.0, 1.0] },
+    BloomVertex{ position: [1.0, 1.0] },
+    BloomVertex{ position: [1.0, -1.0] },
+    BloomVertex{ position: [-1.0, -1.0] }];
+
+/// Copies the high dynamic range frame onto the window. With bloom, the frame's bright pass is
+/// drawn into a half resolution glow target, blurred back & forth with a second target, then
+/// added as the frame is copied
+pub struct BloomBrush<R: Resources, F: Factory<R>> {
+    bright_pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = brightpipe),
+    blur_pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = blurpipe),
+    composite_pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = compositepipe),
+    slice: Slice<R>,
+    bright_data: brightpipe::Data<R>,
+    /// blurs the glow target into the spare target, & the spare target back
+    blur_data: [blurpipe::Data<R>; 2],
+    composite_data: compositepipe::Data<R>,
+    texel_size: [f32; 2],
+}
+
+impl<R: Resources, F: Factory<R> + Clone> BloomBrush<R, F> {
+    /// :scene the frame drawn in `ColorFormat`, copied onto the `target` window
+    pub fn new(mut factory: F,
+               scene: &handle::ShaderResourceView<R, [f32; 4]>,
+               target: &handle::RenderTargetView<R, WindowFormat>)
+               -> BloomBrush<R, F>
+    {
+        let (width, height, ..) = target.get_dimensions();
+        let (width, height) = ((width / 2).max(1), (height / 2).max(1));
+        let (_, glow, glow_target) = factory.create_render_target::<BloomFormat>(width, height)
+            .expect("Bloom glow target");
+        let (_, spare, spare_target) = factory.create_render_target::<BloomFormat>(width, height)
+            .expect("Bloom spare target");
+        let sampler = factory.create_sampler_linear();
+
+        let (vertex_buffer, slice) = factory
+            .create_vertex_buffer_with_slice(&BLOOM_QUAD, &[0u16, 1, 2, 0, 2, 3] as &[u16]);
+        let blur_params = factory.create_constant_buffer(1);
+        let bright_data = brightpipe::Data {
+            vbuf: vertex_buffer.clone(),
+            params: factory.create_constant_buffer(1),
+            scene: (scene.clone(), sampler.clone()),
+            out: glow_target.clone(),
+        };
+        let blur_data = [
+            blurpipe::Data {
+                vbuf: vertex_buffer.clone(),
+                params: blur_params.clone(),
+                source: (glow.clone(), sampler.clone()),
+                out: spare_target,
+            },
+            blurpipe::Data {
+                vbuf: vertex_buffer.clone(),
+                params: blur_params,
+                source: (spare, sampler.clone()),
+                out: glow_target,
+            }];
+        let composite_data = compositepipe::Data {
+            vbuf: vertex_buffer,
+            params: factory.create_constant_buffer(1),
+            scene: (scene.clone(), sampler.clone()),
+            glow: (glow, sampler),
+            out: target.clone(),
+        };
+
+        let bright_pso_cell = debug_watcher_pso_cell!(
+            pipe = brightpipe,
+            vertex_shader = "shader/quad_vert.glsl",
+            fragment_shader = "shader/bright_frag.glsl",
+            factory = factory.clone()).expect("Bloom bright pso");
+        let blur_pso_cell = debug_watcher_pso_cell!(
+            pipe = blurpipe,
+            vertex_shader = "shader/quad_vert.glsl",
+            fragment_shader = "shader/blur_frag.glsl",
+            factory = factory.clone()).expect("Bloom blur pso");
+        let composite_pso_cell = debug_watcher_pso_cell!(
+            pipe = compositepipe,
+            vertex_shader = "shader/quad_vert.glsl",
+            fragment_shader = "shader/composite_frag.glsl",
+            factory = factory).expect("Bloom composite pso");
+
+        BloomBrush {
+            bright_pso_cell,
+            blur_pso_cell,
+            composite_pso_cell,
+            slice,
+            bright_data,
+            blur_data,
+            composite_data,
+            texel_size: [1.0 / f32::from(width), 1.0 / f32::from(height)],
+        }
+    }
+
+    /// Draws the frame onto the window, blooming if `bloom`. Call once the whole frame is drawn
+    pub fn draw<C>(&mut self, encoder: &mut Encoder<R, C>, bloom: bool) where C: CommandBuffer<R> {
+        if bloom {
+            encoder.update_constant_buffer(&self.bright_data.params, &BrightParams { threshold: BRIGHT_THRESHOLD });
+            encoder.draw(&self.slice, self.bright_pso_cell.pso(), &self.bright_data);
+
+            // each pass blurs horizontally then vertically
+            for pass in 0..BLUR_PASSES {
+                let (horizontal, vertical) = blur_steps(self.texel_size, pass);
+                for (data, texel_step) in self.blur_data.iter().zip(&[horizontal, vertical]) {
+                    encoder.update_constant_buffer(&data.params, &BlurParams { texel_step: *texel_step });
+                    encoder.draw(&self.slice, self.blur_pso_cell.pso(), data);
+                }
+            }
+        }
+
+        let strength = if bloom { 1.0 } else { 0.0 };
+        encoder.update_constant_buffer(&self.composite_data.params, &CompositeParams { strength });
+        encoder.draw(&self.slice, self.composite_pso_cell.pso(), &self.composite_data);
+    }
+}
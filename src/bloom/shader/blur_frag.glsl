@@ -0,0 +1,25 @@
+#version 330 core
+
+layout(std140) uniform;
+
+uniform blur_params {
+    vec2 texel_step;
+};
+
+uniform sampler2D source;
+
+in vec2 uv;
+
+out vec4 out_color;
+
+// 9 tap gaussian, center then each side
+const float weights[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+void main() {
+    vec3 sum = texture(source, uv).rgb * weights[0];
+    for (int i = 1; i < 5; i++) {
+        sum += texture(source, uv + texel_step * float(i)).rgb * weights[i];
+        sum += texture(source, uv - texel_step * float(i)).rgb * weights[i];
+    }
+    out_color = vec4(sum, 0.0);
+}
@@ -0,0 +1,19 @@
+#version 330 core
+
+layout(std140) uniform;
+
+uniform bright_params {
+    float threshold;
+};
+
+uniform sampler2D scene;
+
+in vec2 uv;
+
+out vec4 out_color;
+
+void main() {
+    // only light beyond the threshold blooms
+    vec3 color = texture(scene, uv).rgb;
+    out_color = vec4(max(color - vec3(threshold), vec3(0.0)), 0.0);
+}
@@ -0,0 +1,19 @@
+#version 330 core
+
+layout(std140) uniform;
+
+uniform composite_params {
+    float strength;
+};
+
+uniform sampler2D scene;
+uniform sampler2D glow;
+
+in vec2 uv;
+
+out vec4 out_color;
+
+void main() {
+    vec4 color = texture(scene, uv);
+    out_color = vec4(color.rgb + texture(glow, uv).rgb * strength, 1.0);
+}
@@ -0,0 +1,327 @@
+//! Cameras viewing the world, each rendering into its own viewport of the window
+pub mod render;
+
+use cgmath::*;
+use state::rotate;
+use uuid::Uuid;
+
+/// Window area a camera renders into, in pixels from the window's top left
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub left: u32,
+    pub top: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    pub fn fullscreen(width: u32, height: u32) -> Viewport {
+        Viewport { left: 0, top: 0, width, height }
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        let (left, top) = (self.left as i32, self.top as i32);
+        x >= left && y >= top && x < left + self.width as i32 && y < top + self.height as i32
+    }
+
+    /// Window pixel relative to the viewport's top left
+    pub fn local(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (x - self.left as i32, y - self.top as i32)
+    }
+}
+
+/// Viewports splitting a window between `count` cameras, in rows of up to 2 columns or a grid
+/// of as many columns as rows
+pub fn split_viewports(width: u32, height: u32, count: usize) -> Vec<Viewport> {
+    let count = count.max(1) as u32;
+    let mut columns = (count as f32).sqrt().ceil() as u32;
+    if count == 2 {
+        columns = 2;
+    }
+    let rows = (count as f32 / columns as f32).ceil() as u32;
+    (0..count).map(|idx| {
+        let (column, row) = (idx % columns, idx / columns);
+        let (left, top) = (width * column / columns, height * row / rows);
+        Viewport {
+            left,
+            top,
+            width: width * (column + 1) / columns - left,
+            height: height * (row + 1) / rows - top,
+        }
+    }).collect()
+}
+
+/// Reference frame pivoting on the center of mass of two bodies & rotating with the line
+/// joining them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RotatingFrame {
+    /// (a, b) bodies, the frame's x axis points from a to b
+    pub bodies: (Uuid, Uuid),
+    pub pivot: Vector2<f64>,
+    /// anti-clockwise radians
+    pub angle: f64,
+}
+
+impl RotatingFrame {
+    /// Position relative to the frame of a world location
+    pub fn to_local(&self, world: Vector2<f64>) -> Vector2<f64> {
+        rotate(world - self.pivot, -self.angle)
+    }
+
+    /// World location of a position relative to the frame
+    pub fn to_world(&self, local: Vector2<f64>) -> Vector2<f64> {
+        self.pivot + rotate(local, self.angle)
+    }
+}
+
+fn birds_eye_at_z(height: f32) -> Matrix4<f32> {
+    let mut view = Matrix4::identity();
+    view.z.z = height;
+    view
+}
+
+/// Where a viewport looks at the world. Screen pixels are relative to the viewport's top left
+#[derive(Clone, Debug)]
+pub struct Camera {
+    pub origin: Vector2<f32>,
+    pub zoom: f32,
+    /// anti-clockwise camera rotation in radians, the world appears rotated the opposite way
+    pub rotation: f32,
+    pub viewport: Viewport,
+    /// body the camera is following
+    pub following: Option<Uuid>,
+    /// co-rotating frame the camera is viewing, if any
+    pub frame: Option<RotatingFrame>,
+}
+
+impl Camera {
+    pub fn new(viewport: Viewport) -> Camera {
+        Camera {
+            origin: Vector2::new(0.0, 0.0),
+            zoom: 16.0,
+            rotation: 0.0,
+            viewport,
+            following: None,
+            frame: None,
+        }
+    }
+
+    /// Birds eye view rotating the world around the origin by the camera rotation
+    pub fn view(&self) -> Matrix4<f32> {
+        let view = birds_eye_at_z(1.0);
+        if self.rotation == 0.0 {
+            return view;
+        }
+        let origin = self.origin.extend(0.0);
+        view * Matrix4::from_translation(origin) *
+            Matrix4::from_angle_z(Rad(-self.rotation)) *
+            Matrix4::from_translation(-origin)
+    }
+
+    pub fn projection(&self) -> Matrix4<f32> {
+        ortho(self.origin.x - self.zoom * self.aspect_ratio(),
+              self.origin.x + self.zoom * self.aspect_ratio(),
+              self.origin.y - self.zoom,
+              self.origin.y + self.zoom,
+              1.0,
+              -1.0)
+    }
+
+    /// World distance across a screen pixel
+    pub fn world_per_pixel(&self) -> f32 {
+        self.zoom * 2.0 / self.viewport.height as f32
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.viewport.width as f32 / self.viewport.height as f32
+    }
+
+    /// translates screen pixels into world co-ordinates in the orthographic projection
+    pub fn screen_to_world_normalised<V: Into<Vector2<i32>>>(&self, pixels: V) -> Vector2<f32> {
+        let pixels = pixels.into();
+        let (width, height) = (self.viewport.width as f32, self.viewport.height as f32);
+        let x_world = self.zoom * self.aspect_ratio() * (pixels.x as f32 * 2.0 / width - 1f32);
+        let y_world = self.zoom * (-pixels.y as f32 * 2.0 / height + 1f32);
+        rotate(Vector2::new(x_world, y_world), self.rotation)
+    }
+
+    pub fn screen_to_world<V: Into<Vector2<i32>>>(&self, pixels: V) -> Vector2<f32> {
+        self.origin + self.screen_to_world_normalised(pixels)
+    }
+
+    /// World location under a window pixel
+    pub fn window_to_world(&self, pixel: (i32, i32)) -> Vector2<f32> {
+        self.screen_to_world(self.viewport.local(pixel))
+    }
+
+    /// Screen pixel position of a world location, the inverse of `screen_to_world`
+    pub fn world_to_screen(&self, world: Vector2<f32>) -> Vector2<f32> {
+        let normalised = rotate(world - self.origin, -self.rotation);
+        Vector2::new(
+            (normalised.x / (self.zoom * self.aspect_ratio()) + 1.0) * self.viewport.width as f32 / 2.0,
+            (1.0 - normalised.y / self.zoom) * self.viewport.height as f32 / 2.0)
+    }
+
+    /// Returns tuple with (min, max) coord corners
+    /// - left: bottom left, least x & y visible world location
+    /// - right: top right, most x & y visible world location
+    pub fn visible_world_range(&self) -> (Vector2<f32>, Vector2<f32>) {
+        let (w, h) = (self.viewport.width as i32, self.viewport.height as i32);
+        let bottom_left = self.screen_to_world(Vector2::new(0, h));
+        let top_right = self.screen_to_world(Vector2::new(w, 0));
+        if self.rotation == 0.0 {
+            return (bottom_left, top_right);
+        }
+
+        // rotated view, so bound all corners
+        let top_left = self.screen_to_world(Vector2::new(0, 0));
+        let bottom_right = self.screen_to_world(Vector2::new(w, h));
+        let corners = [bottom_left, top_right, top_left, bottom_right];
+        let min = corners.iter().fold(corners[0], |min, c| Vector2::new(min.x.min(c.x), min.y.min(c.y)));
+        let max = corners.iter().fold(corners[0], |max, c| Vector2::new(max.x.max(c.x), max.y.max(c.y)));
+        (min, max)
+    }
+}
+
+#[cfg(test)]
+mod camera_test {
+    use super::*;
+    use std::f32;
+
+    fn camera(width: u32, height: u32) -> Camera {
+        Camera::new(Viewport::fullscreen(width, height))
+    }
+
+    // see https://github.com/gfx-rs/gfx/tree/master/src/backend/gl
+    // (0,0)
+    //     ┌─┐
+    //     └─┘
+    //        (width-px, height-px)
+    //      |
+    //      v
+    // (-1az,1z)
+    //     ┌─┐
+    //     └─┘
+    //        (1az,-1z)
+    // :a aspect ratio
+    // :z zoom
+    fn test_screen_to_world(c: Camera) {
+        let a = c.aspect_ratio();
+        let z = c.zoom;
+        let (w, h) = (c.viewport.width as i32, c.viewport.height as i32);
+        assert_eq!(c.screen_to_world(Vector2::new(0, 0)),
+            Vector2::new(-a * z, 1f32 * z), "top-left");
+        assert_eq!(c.screen_to_world(Vector2::new(w, 0)),
+            Vector2::new(a * z, 1f32 * z), "top-right");
+        assert_eq!(c.screen_to_world(Vector2::new(0, h)),
+            Vector2::new(-a * z, -1f32 * z), "bottom-left");
+        assert_eq!(c.screen_to_world(Vector2::new(w, h)),
+            Vector2::new(a * z, -1f32 * z), "bottom-right");
+        assert_eq!(c.screen_to_world(Vector2::new(w / 2, h / 2)),
+            Vector2::new(0f32, 0f32), "center");
+    }
+
+    #[test]
+    fn rotating_frame_round_trip() {
+        let frame = RotatingFrame { bodies: (Uuid::new_v4(), Uuid::new_v4()), pivot: Vector2::new(1.0, 2.0), angle: 0.5 };
+        let world = Vector2::new(-3.0, 4.0);
+        assert!(frame.to_world(frame.to_local(world)).distance(world) < 1e-12);
+        assert!(frame.to_local(frame.pivot).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn world_to_screen_inverts_screen_to_world() {
+        let mut camera = camera(160, 90);
+        camera.origin = Vector2::new(3.0, -2.0);
+        camera.zoom = 5.0;
+        camera.rotation = 0.7;
+        let screen = camera.world_to_screen(camera.screen_to_world(Vector2::new(40, 70)));
+        assert!(screen.distance(Vector2::new(40.0, 70.0)) < 0.001, "{:?}", screen);
+    }
+
+    #[test]
+    fn screen_to_world_zoom_1_aspect_1() {
+        //      | simplifies to
+        //      v
+        // (-1,1)
+        //     ┌─┐
+        //     └─┘
+        //        (1,-1)
+        test_screen_to_world(camera(100, 100));
+    }
+
+    #[test]
+    fn screen_to_world_zoom_1() {
+        //      | simplifies to
+        //      v
+        // (-1a,1)
+        //     ┌─┐
+        //     └─┘
+        //        (1a,-1)
+        test_screen_to_world(camera(160, 90));
+    }
+
+    #[test]
+    fn screen_to_world() {
+        //      | simplifies to
+        //      v
+        // (-1a,1)
+        //     ┌─┐
+        //     └─┘
+        //        (1a,-1)
+        let mut camera = camera(160, 90);
+        camera.zoom = 0.33f32;
+        test_screen_to_world(camera);
+    }
+
+    #[test]
+    fn visible_world_range() {
+        let mut camera = camera(180, 90);
+        camera.zoom = 3f32;
+        assert_eq!(camera.visible_world_range(), ((-6_f32, -3_f32).into(), (6_f32, 3_f32).into()));
+    }
+
+    #[test]
+    fn screen_to_world_rotated() {
+        let mut camera = camera(200, 100);
+        camera.zoom = 2.0;
+        camera.rotation = f32::consts::FRAC_PI_2;
+
+        // screen right is world up with a quarter anti-clockwise camera turn
+        let right_middle = camera.screen_to_world(Vector2::new(200, 50));
+        assert!(right_middle.distance(Vector2::new(0.0, 4.0)) < 0.0001, "{:?}", right_middle);
+
+        let (min, max) = camera.visible_world_range();
+        assert!(min.distance(Vector2::new(-2.0, -4.0)) < 0.0001, "{:?}", min);
+        assert!(max.distance(Vector2::new(2.0, 4.0)) < 0.0001, "{:?}", max);
+    }
+
+    #[test]
+    fn view_rotates_around_origin() {
+        let mut camera = camera(100, 100);
+        camera.origin = Vector2::new(5.0, 5.0);
+        camera.rotation = 1.234;
+
+        let origin = camera.view() * Vector4::new(5.0, 5.0, 0.0, 1.0);
+        assert!(origin.truncate().truncate().distance(camera.origin) < 0.0001);
+
+        // a world point under the cursor is drawn back at that cursor position
+        let world = camera.screen_to_world(Vector2::new(80, 30));
+        let clip = camera.projection() * camera.view() * world.extend(0.0).extend(1.0);
+        assert!(clip.truncate().truncate().distance(Vector2::new(0.6, 0.4)) < 0.0001, "{:?}", clip);
+    }
+
+    #[test]
+    fn viewports_split_window() {
+        assert_eq!(split_viewports(200, 100, 1), vec!(Viewport::fullscreen(200, 100)));
+        assert_eq!(split_viewports(201, 100, 2), vec!(
+            Viewport { left: 0, top: 0, width: 100, height: 100 },
+            Viewport { left: 100, top: 0, width: 101, height: 100 }));
+
+        let quarters = split_viewports(200, 100, 4);
+        assert_eq!(quarters[3], Viewport { left: 100, top: 50, width: 100, height: 50 });
+        assert!(quarters[3].contains((150, 99)));
+        assert!(!quarters[3].contains((150, 49)));
+        assert_eq!(quarters[3].local((150, 60)), (50, 10));
+    }
+}
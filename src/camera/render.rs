@@ -0,0 +1,103 @@
+use super::*;
+use super::super::{ColorFormat, DepthFormat};
+use gfx::*;
+use gfx::traits::FactoryExt;
+use gfx;
+use gfx_shader_watch::*;
+
+#[derive(VertexData, Debug, Clone, Copy)]
+pub struct ViewportVertex {
+    position: [f32; 2],
+    uv: [f32; 2],
+}
+
+gfx_defines! {
+    pipeline viewportpipe {
+        vbuf: VertexBuffer<ViewportVertex> = (),
+        scene: TextureSampler<[f32; 4]> = "scene",
+        out: RenderTarget<ColorFormat> = "out_color",
+    }
+}
+
+/// Triangle list vertices covering `viewport` on a window `width` x `height` pixels, mapping
+/// the whole scene texture onto it
+fn viewport_vertices(viewport: &Viewport, (width, height): (u32, u32)) -> [ViewportVertex; 6] {
+    let ndc = |x: u32, y: u32| [x as f32 / width as f32 * 2.0 - 1.0, 1.0 - y as f32 / height as f32 * 2.0];
+    let (right, bottom) = (viewport.left + viewport.width, viewport.top + viewport.height);
+    let top_left = ViewportVertex { position: ndc(viewport.left, viewport.top), uv: [0.0, 1.0] };
+    let top_right = ViewportVertex { position: ndc(right, viewport.top), uv: [1.0, 1.0] };
+    let bottom_left = ViewportVertex { position: ndc(viewport.left, bottom), uv: [0.0, 0.0] };
+    let bottom_right = ViewportVertex { position: ndc(right, bottom), uv: [1.0, 0.0] };
+    [bottom_left, bottom_right, top_right, top_right, top_left, bottom_left]
+}
+
+/// Offscreen targets a camera draws its viewport into, before it's copied onto the window
+pub struct ViewportTarget<R: Resources> {
+    pub viewport: Viewport,
+    pub color: handle::RenderTargetView<R, ColorFormat>,
+    pub depth: handle::DepthStencilView<R, DepthFormat>,
+    scene: handle::ShaderResourceView<R, [f32; 4]>,
+    vbuf: handle::Buffer<R, ViewportVertex>,
+    slice: Slice<R>,
+}
+
+impl<R: Resources> ViewportTarget<R> {
+    pub fn new<F: Factory<R>>(factory: &mut F, viewport: Viewport, window: (u32, u32)) -> ViewportTarget<R> {
+        let (width, height) = (viewport.width as u16, viewport.height as u16);
+        let (_, scene, color) = factory.create_render_target::<ColorFormat>(width, height)
+            .expect("Viewport color target");
+        let depth = factory.create_depth_stencil_view_only::<DepthFormat>(width, height)
+            .expect("Viewport depth target");
+        let (vbuf, slice) = factory.create_vertex_buffer_with_slice(&viewport_vertices(&viewport, window), ());
+        ViewportTarget { viewport, color, depth, scene, vbuf, slice }
+    }
+}
+
+/// Copies viewport targets onto their area of the window
+pub struct ViewportBrush<R: Resources, F: Factory<R>> {
+    pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = viewportpipe),
+    sampler: handle::Sampler<R>,
+    out: handle::RenderTargetView<R, ColorFormat>,
+}
+
+impl<R: Resources, F: Factory<R> + Clone> ViewportBrush<R, F> {
+    pub fn new(mut factory: F, target: &handle::RenderTargetView<R, ColorFormat>) -> ViewportBrush<R, F> {
+        // targets match their viewport's pixels, so no filtering is needed
+        let sampler = factory.create_sampler(texture::SamplerInfo::new(texture::FilterMethod::Scale,
+                                                                       texture::WrapMode::Clamp));
+        let pso_cell = debug_watcher_pso_cell!(
+            pipe = viewportpipe,
+            vertex_shader = "shader/vert.glsl",
+            fragment_shader = "shader/frag.glsl",
+            factory = factory).expect("Viewport pso");
+
+        ViewportBrush { pso_cell, sampler, out: target.clone() }
+    }
+
+    pub fn draw<C>(&mut self, encoder: &mut Encoder<R, C>, target: &ViewportTarget<R>) where C: CommandBuffer<R> {
+        let data = viewportpipe::Data {
+            vbuf: target.vbuf.clone(),
+            scene: (target.scene.clone(), self.sampler.clone()),
+            out: self.out.clone(),
+        };
+        encoder.draw(&target.slice, self.pso_cell.pso(), &data);
+    }
+}
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+
+    #[test]
+    fn viewport_quad_covers_its_window_area() {
+        let viewport = Viewport { left: 512, top: 0, width: 512, height: 384 };
+        let verts = viewport_vertices(&viewport, (1024, 768));
+        let xs: Vec<_> = verts.iter().map(|v| v.position[0]).collect();
+        let ys: Vec<_> = verts.iter().map(|v| v.position[1]).collect();
+        assert!(xs.iter().all(|&x| x == 0.0 || x == 1.0), "{:?}", xs);
+        assert!(ys.iter().all(|&y| y == 0.0 || y == 1.0), "{:?}", ys);
+        // top of the viewport shows the top of the scene texture
+        let top_left = verts.iter().find(|v| v.position == [0.0, 1.0]).unwrap();
+        assert_eq!(top_left.uv, [0.0, 1.0]);
+    }
+}
@@ -0,0 +1,11 @@
+#version 330 core
+
+uniform sampler2D scene;
+
+in vec2 scene_uv;
+
+out vec4 out_color;
+
+void main() {
+    out_color = texture(scene, scene_uv);
+}
@@ -0,0 +1,12 @@
+#version 330 core
+
+in vec2 position;
+in vec2 uv;
+
+out vec2 scene_uv;
+
+void main() {
+    scene_uv = uv;
+    // already in normalised device coordinates
+    gl_Position = vec4(position, 0.0, 1.0);
+}
@@ -0,0 +1,350 @@
+//! Keyframed camera paths, played back through a camera's `CameraTasks` for reproducible showcase footage
+//!
+//! One keyframe per line as whitespace separated `key=value` pairs, each easing the camera
+//! from the previous keyframe (or the camera when playback started) over its duration.
+//! A `loop` line repeats the path, blank lines & anything after a `#` are ignored
+//! ```text
+//! loop
+//! origin=0,0 zoom=16 duration=2 ease=expo_in_out
+//! follow=2   zoom=4  duration=3 ease=cubic_out   # 2nd body, or a body uuid
+//! follow=2            duration=1.5              # hold, zoom & easing are optional
+//! ```
+use cgmath::*;
+use easer::functions::*;
+use ease::Easer;
+use camera::Camera;
+use orbitbody::OrbitBody;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use uuid::Uuid;
+
+pub type EasingFn = fn(f32, f32, f32, f32) -> f32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CameraPathError {
+    /// 1-based line number the error occurred on, 0 if not line specific
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for CameraPathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.reason)
+        }
+        else {
+            write!(f, "line {}: {}", self.line, self.reason)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BodyRef {
+    /// 0-based index into the state's bodies
+    Index(usize),
+    Id(Uuid),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KeyTarget {
+    Origin(Vector2<f32>),
+    Follow(BodyRef),
+}
+
+#[derive(Clone, Debug)]
+pub struct Keyframe {
+    pub target: KeyTarget,
+    /// zoom to ease to, `None` keeps the previous keyframe's zoom
+    pub zoom: Option<f32>,
+    /// seconds taken to reach this keyframe
+    pub duration: f32,
+    pub easing: EasingFn,
+}
+
+#[derive(Clone, Debug)]
+pub struct CameraPath {
+    pub keyframes: Vec<Keyframe>,
+    pub looping: bool,
+}
+
+impl CameraPath {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CameraPath, CameraPathError> {
+        let mut contents = String::new();
+        File::open(path.as_ref())
+            .and_then(|mut file| file.read_to_string(&mut contents))
+            .map_err(|err| CameraPathError {
+                line: 0,
+                reason: format!("Could not read {}: {}", path.as_ref().display(), err),
+            })?;
+        CameraPath::parse(&contents)
+    }
+
+    pub fn parse(config: &str) -> Result<CameraPath, CameraPathError> {
+        let mut path = CameraPath { keyframes: Vec::new(), looping: false };
+        for (idx, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line == "loop" {
+                path.looping = true;
+                continue;
+            }
+            let keyframe = parse_keyframe(line)
+                .map_err(|reason| CameraPathError { line: idx + 1, reason })?;
+            path.keyframes.push(keyframe);
+        }
+        if path.keyframes.is_empty() {
+            return Err(CameraPathError { line: 0, reason: "No keyframes defined".into() });
+        }
+        Ok(path)
+    }
+
+    /// Seconds to play through all keyframes once
+    pub fn duration(&self) -> f32 {
+        self.keyframes.iter().map(|k| k.duration).sum()
+    }
+}
+
+fn parse_keyframe(line: &str) -> Result<Keyframe, String> {
+    let mut target = None;
+    let mut zoom = None;
+    let mut duration = None;
+    let mut easing: EasingFn = Linear::ease_in_out;
+
+    for pair in line.split_whitespace() {
+        let mut kv = pair.splitn(2, '=');
+        let (key, val) = match (kv.next(), kv.next()) {
+            (Some(key), Some(val)) => (key, val),
+            _ => return Err(format!("Expected `key=value`, got `{}`", pair)),
+        };
+        let number = |val: &str| match val.parse::<f32>() {
+            Ok(n) if n.is_finite() => Ok(n),
+            _ => Err(format!("Invalid number `{}` for {}", val, key)),
+        };
+        match key {
+            "origin" => {
+                let xy = val.split(',').map(&number).collect::<Result<Vec<f32>, String>>()?;
+                if xy.len() != 2 {
+                    return Err(format!("Expected origin=x,y, got `{}`", val));
+                }
+                target = Some(KeyTarget::Origin(Vector2::new(xy[0], xy[1])));
+            }
+            "follow" => target = Some(KeyTarget::Follow(match val.parse::<usize>() {
+                Ok(0) => return Err("Body numbers start at 1".into()),
+                Ok(number) => BodyRef::Index(number - 1),
+                Err(_) => BodyRef::Id(Uuid::parse_str(val)
+                    .map_err(|_| format!("Expected a body number or uuid, got `{}`", val))?),
+            })),
+            "zoom" => match number(val)? {
+                z if z > 0.0 => zoom = Some(z),
+                _ => return Err("zoom must be positive".into()),
+            },
+            "duration" => match number(val)? {
+                d if d >= 0.0 => duration = Some(d),
+                _ => return Err("duration must not be negative".into()),
+            },
+            "ease" => easing = easing_from_name(val)
+                .ok_or_else(|| format!("Unknown easing `{}`", val))?,
+            _ => return Err(format!("Unknown key `{}`", key)),
+        }
+    }
+
+    Ok(Keyframe {
+        target: target.ok_or("Missing origin=x,y or follow=body")?,
+        zoom,
+        duration: duration.ok_or("Missing duration")?,
+        easing,
+    })
+}
+
+/// Returns an `easer::functions` easing by name, ie `linear`, `expo_out`, `cubic_in_out`
+pub fn easing_from_name(name: &str) -> Option<EasingFn> {
+    macro_rules! easings {
+        ($($prefix:expr => $function:ident),*) => {
+            $(
+                if name == concat!($prefix, "_in") { return Some($function::ease_in); }
+                if name == concat!($prefix, "_out") { return Some($function::ease_out); }
+                if name == concat!($prefix, "_in_out") { return Some($function::ease_in_out); }
+            )*
+        }
+    }
+    if name == "linear" {
+        return Some(Linear::ease_in_out);
+    }
+    easings!("back" => Back, "bounce" => Bounce, "circ" => Circ, "cubic" => Cubic,
+             "elastic" => Elastic, "expo" => Expo, "quad" => Quad, "quart" => Quart,
+             "quint" => Quint, "sine" => Sine);
+    None
+}
+
+/// Playback progress through a `CameraPath`
+#[derive(Clone, Debug)]
+pub struct PathPlayback {
+    pub path: CameraPath,
+    /// seconds of playback, not advanced while paused. Looping wraps it within the 2nd play through
+    pub elapsed: f32,
+    pub paused: bool,
+    /// (zoom, origin) of the camera when playback started, eased from to the first keyframe
+    start: (f32, Vector2<f32>),
+}
+
+impl PathPlayback {
+    pub fn new(path: CameraPath, camera: &Camera) -> PathPlayback {
+        PathPlayback { path, elapsed: 0.0, paused: false, start: (camera.zoom, camera.origin) }
+    }
+
+    pub fn advance(&mut self, delta: f32) {
+        if !self.paused {
+            self.elapsed += delta;
+            let total = self.path.duration();
+            if self.path.looping && total > 0.0 && self.elapsed >= 2.0 * total {
+                // stay within the second play through, so long loops keep their precision
+                self.elapsed = total + (self.elapsed - total) % total;
+            }
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        !self.path.looping && self.elapsed >= self.path.duration()
+    }
+
+    /// Returns the (zoom, origin) camera at the current playback time
+    pub fn camera(&self, bodies: &[OrbitBody]) -> (f32, Vector2<f32>) {
+        let total = self.path.duration();
+        let mut time = self.elapsed;
+        let mut from = self.start;
+        if self.path.looping && total > 0.0 && time >= total {
+            // later loops ease from the last keyframe
+            from = self.keyframe_camera(self.path.keyframes.len() - 1, bodies);
+            time %= total;
+        }
+
+        for keyframe in &self.path.keyframes {
+            let to = PathPlayback::towards(keyframe, from, bodies);
+            if time < keyframe.duration {
+                let vals = Easer::using(keyframe.easing)
+                    .duration(keyframe.duration)
+                    .add_transition(from.0, to.0)
+                    .add_transition(from.1.x, to.1.x)
+                    .add_transition(from.1.y, to.1.y)
+                    .values_at(time);
+                return (vals[0], Vector2::new(vals[1], vals[2]));
+            }
+            time -= keyframe.duration;
+            from = to;
+        }
+        from
+    }
+
+    /// Camera reached at the end of a keyframe in the first play through
+    fn keyframe_camera(&self, idx: usize, bodies: &[OrbitBody]) -> (f32, Vector2<f32>) {
+        self.path.keyframes[..idx + 1].iter()
+            .fold(self.start, |from, keyframe| PathPlayback::towards(keyframe, from, bodies))
+    }
+
+    /// Camera a keyframe eases to, keeping the previous zoom or origin where unspecified or the
+    /// followed body is gone
+    fn towards(keyframe: &Keyframe, from: (f32, Vector2<f32>), bodies: &[OrbitBody]) -> (f32, Vector2<f32>) {
+        let origin = match keyframe.target {
+            KeyTarget::Origin(origin) => Some(origin),
+            KeyTarget::Follow(BodyRef::Index(idx)) => bodies.get(idx).map(|b| b.center.cast()),
+            KeyTarget::Follow(BodyRef::Id(id)) => bodies.iter().find(|b| b.id == id).map(|b| b.center.cast()),
+        };
+        (keyframe.zoom.unwrap_or(from.0), origin.unwrap_or(from.1))
+    }
+}
+
+#[cfg(test)]
+mod camerapath_test {
+    use super::*;
+    use state::State;
+
+    #[test]
+    fn parse_keyframes() {
+        let path = CameraPath::parse("
+            # intro
+            loop
+            origin=1.5,-2 zoom=8 duration=2 ease=expo_in_out
+            follow=2 duration=0.5 # hold zoom
+        ").unwrap();
+
+        assert!(path.looping);
+        assert_eq!(path.keyframes.len(), 2);
+        assert_eq!(path.keyframes[0].target, KeyTarget::Origin(Vector2::new(1.5, -2.0)));
+        assert_eq!(path.keyframes[0].zoom, Some(8.0));
+        assert_eq!(path.keyframes[1].target, KeyTarget::Follow(BodyRef::Index(1)));
+        assert_eq!(path.keyframes[1].zoom, None);
+        assert_eq!(path.duration(), 2.5);
+    }
+
+    #[test]
+    fn showcase_path_parses() {
+        let path = CameraPath::parse(include_str!("../camera-paths/showcase.txt")).unwrap();
+        assert!(path.duration() > 0.0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(CameraPath::parse("\nzoom=2 duration=1").unwrap_err().line, 2);
+        assert_eq!(CameraPath::parse("origin=0,0 duration=1 ease=wobbly").unwrap_err().reason,
+                   "Unknown easing `wobbly`");
+        assert_eq!(CameraPath::parse("follow=0 duration=1").unwrap_err().line, 1);
+        assert_eq!(CameraPath::parse("origin=0 duration=1").unwrap_err().line, 1);
+        assert_eq!(CameraPath::parse("loop").unwrap_err().line, 0);
+        assert_eq!(CameraPath::parse("origin=0,0 duration=inf").unwrap_err().reason,
+                   "Invalid number `inf` for duration");
+        assert_eq!(CameraPath::parse("origin=0,0 zoom=NaN duration=1").unwrap_err().reason,
+                   "Invalid number `NaN` for zoom");
+    }
+
+    #[test]
+    fn playback_eases_between_keyframes() {
+        let mut state = State::new(200, 100);
+        state.camera_mut().zoom = 10.0;
+        state.drawables.orbit_bodies = vec!(OrbitBody::new((4.0, 4.0).into(), (0.0, 0.0).into(), 1.0, 1.0));
+        let path = CameraPath::parse("
+            origin=2,0 zoom=20 duration=1 ease=linear
+            follow=1 duration=2 ease=linear
+        ").unwrap();
+        let mut playback = PathPlayback::new(path, state.camera());
+
+        playback.advance(0.5);
+        assert_eq!(playback.camera(&state.drawables.orbit_bodies), (15.0, Vector2::new(1.0, 0.0)));
+        playback.paused = true;
+        playback.advance(10.0);
+        assert_eq!(playback.elapsed, 0.5);
+
+        playback.paused = false;
+        playback.advance(1.5);
+        assert_eq!(playback.camera(&state.drawables.orbit_bodies), (20.0, Vector2::new(3.0, 2.0)));
+        assert!(!playback.finished());
+        playback.advance(1.0);
+        assert!(playback.finished());
+        assert_eq!(playback.camera(&state.drawables.orbit_bodies), (20.0, Vector2::new(4.0, 4.0)));
+    }
+
+    #[test]
+    fn looping_eases_from_last_keyframe() {
+        let state = State::new(200, 100);
+        let mut path = CameraPath::parse("
+            origin=10,0 zoom=4 duration=1 ease=linear
+            origin=20,0 duration=1 ease=linear
+        ").unwrap();
+        path.looping = true;
+        let mut playback = PathPlayback::new(path, state.camera());
+
+        playback.advance(2.5);
+        assert!(!playback.finished());
+        assert_eq!(playback.camera(&state.drawables.orbit_bodies), (4.0, Vector2::new(15.0, 0.0)));
+
+        // many loops later
+        for _ in 0..10_000 {
+            playback.advance(1.0);
+        }
+        assert!(playback.elapsed < 4.0, "{}", playback.elapsed);
+        assert_eq!(playback.camera(&state.drawables.orbit_bodies), (4.0, Vector2::new(15.0, 0.0)));
+    }
+}
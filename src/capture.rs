@@ -0,0 +1,88 @@
+//! Saves rendered frames as a numbered PNG sequence, ie `frame-00001.png`
+use gfx_device_gl;
+use gfx_gl as gl;
+use image;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+/// Frames captured per second of camera path playback
+pub const CAPTURE_FPS: u32 = 60;
+
+pub struct FrameCapture {
+    dir: PathBuf,
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl FrameCapture {
+    pub fn new<P: AsRef<Path>>(dir: P, width: u32, height: u32) -> io::Result<FrameCapture> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(FrameCapture {
+            dir: dir.as_ref().to_path_buf(),
+            width,
+            height,
+            pixels: vec!(0; (width * height * 4) as usize),
+        })
+    }
+
+    pub fn frame_path(&self, frame: u32) -> PathBuf {
+        self.dir.join(format!("frame-{:05}.png", frame))
+    }
+
+    /// Reads the current back buffer & saves it as a frame, call before swapping buffers
+    pub fn save(&mut self, device: &mut gfx_device_gl::Device, frame: u32) -> io::Result<()> {
+        let (width, height) = (self.width, self.height);
+        let pixels = &mut self.pixels;
+        unsafe {
+            device.with_gl(|gl| {
+                gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+                gl.ReadPixels(0, 0, width as i32, height as i32, gl::RGBA, gl::UNSIGNED_BYTE,
+                              pixels.as_mut_ptr() as *mut _);
+            });
+        }
+        flip_rows(&mut self.pixels, self.width as usize * 4);
+        image::save_buffer(self.frame_path(frame), &self.pixels, self.width, self.height, image::RGBA(8))
+    }
+}
+
+/// GL reads rows bottom up, images are top down
+fn flip_rows(pixels: &mut [u8], row_len: usize) {
+    let rows = pixels.len() / row_len;
+    for row in 0..rows / 2 {
+        let (top, bottom) = pixels.split_at_mut((rows - row - 1) * row_len);
+        let top_row = top[row * row_len..(row + 1) * row_len].iter_mut();
+        for (top_pixel, bottom_pixel) in top_row.zip(bottom[..row_len].iter_mut()) {
+            mem::swap(top_pixel, bottom_pixel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod capture_test {
+    use super::*;
+
+    #[test]
+    fn flips_rows() {
+        let mut pixels = vec!(1, 1, 2, 2, 3, 3);
+        flip_rows(&mut pixels, 2);
+        assert_eq!(pixels, vec!(3, 3, 2, 2, 1, 1));
+
+        let mut pixels = vec!(1, 2, 3, 4);
+        flip_rows(&mut pixels, 1);
+        assert_eq!(pixels, vec!(4, 3, 2, 1));
+    }
+
+    #[test]
+    fn numbered_frame_paths() {
+        let capture = FrameCapture {
+            dir: PathBuf::from("shots"),
+            width: 1,
+            height: 1,
+            pixels: Vec::new(),
+        };
+        assert_eq!(capture.frame_path(42), PathBuf::from("shots/frame-00042.png"));
+    }
+}
@@ -0,0 +1,78 @@
+//! Time source for camera tasks, separating wall time from simulation time
+use time;
+
+#[derive(Clone, Copy, Debug)]
+enum Source {
+    /// wall time read from the system, relative to an epoch
+    System { epoch: f64 },
+    /// wall time only moved by `Clock::advance`, for deterministic tests & captures
+    Manual { wall: f64 },
+}
+
+/// Seconds since the clock was created, as f64 so precision holds for long running processes.
+/// Animations should store times relative to their own start to keep f32 precision.
+#[derive(Clone, Copy, Debug)]
+pub struct Clock {
+    source: Source,
+    /// seconds of simulation, not advanced while paused
+    sim: f64,
+}
+
+impl Clock {
+    pub fn system() -> Clock {
+        Clock { source: Source::System { epoch: time::precise_time_s() }, sim: 0.0 }
+    }
+
+    pub fn manual() -> Clock {
+        Clock { source: Source::Manual { wall: 0.0 }, sim: 0.0 }
+    }
+
+    /// Real seconds since the clock started
+    pub fn wall(&self) -> f64 {
+        match self.source {
+            Source::System { epoch } => time::precise_time_s() - epoch,
+            Source::Manual { wall } => wall,
+        }
+    }
+
+    /// Simulated seconds since the clock started
+    pub fn sim(&self) -> f64 {
+        self.sim
+    }
+
+    /// Advances wall time of a manual clock, a system clock follows real time
+    pub fn advance(&mut self, wall_delta: f64) {
+        if let Source::Manual { ref mut wall } = self.source {
+            *wall += wall_delta;
+        }
+    }
+
+    /// Advances simulation time by a compute step
+    pub fn advance_sim(&mut self, sim_delta: f64) {
+        self.sim += sim_delta;
+    }
+}
+
+#[cfg(test)]
+mod clock_test {
+    use super::*;
+
+    #[test]
+    fn manual_clock() {
+        let mut clock = Clock::manual();
+        clock.advance(0.5);
+        clock.advance(0.25);
+        clock.advance_sim(0.25);
+        assert_eq!(clock.wall(), 0.75);
+        assert_eq!(clock.sim(), 0.25);
+    }
+
+    #[test]
+    fn system_clock_wall_time_is_real() {
+        let mut clock = Clock::system();
+        let before = clock.wall();
+        clock.advance(100.0);
+        assert!(clock.wall() >= before);
+        assert!(clock.wall() < 100.0);
+    }
+}
@@ -1,26 +1,53 @@
 use single_value_channel::*;
 use glutin::*;
 use std::thread;
+use std::sync::mpsc;
 use std::time::Duration;
+use std::f32;
 use input::*;
 use state::*;
 use time;
 use cgmath::*;
 use rayon::prelude::*;
 use seer::*;
+use debug::StepTimings;
+use bindings::{InputMap, Action};
+use camerapath::CameraPath;
+use capture::CAPTURE_FPS;
+use clock::Clock;
+use orbitbody::OrbitBody;
 
 const DESIRED_CPS: u32 = 1_080;
 const DESIRED_DELTA: f64 = 1.0 / DESIRED_CPS as f64;
-const GRAVITY: f64 = 0.01;
+pub const GRAVITY: f64 = 0.01;
 
 #[cfg_attr(feature = "cargo-clippy", allow(float_cmp))]
-pub fn start(initial_state: State, events: EventsLoop) -> Receiver<State> {
+/// Starts the compute loop, playing `camera_path` if provided.
+/// With `capture_acks` computation steps at a fixed rate, sending each state to capture
+/// with a `capture_frame` number & waiting for the frame number to be sent back once saved.
+/// With `benchmark` bodies are never simulated, so only rendering is measured.
+pub fn start(initial_state: State,
+             events: EventsLoop,
+             bindings: InputMap,
+             camera_path: Option<CameraPath>,
+             capture_acks: Option<mpsc::Receiver<u32>>,
+             benchmark: bool) -> Receiver<State> {
     let (latest_state_getter, render_state) = channel_starting_with(initial_state.clone());
 
     thread::spawn(move|| {
-        let mut tasks = Tasks::new();
-        let mut user_mouse = UserMouse::new();
-        let mut user_keys = UserKeys::new();
+        let mut tasks = match capture_acks {
+            // captures run in fixed steps, so animate with the same steps
+            Some(_) => Tasks::with_clock(Clock::manual()),
+            None => Tasks::new(),
+        };
+        let mut user_mouse = UserMouse::new(&bindings);
+        let mut user_keys = UserKeys::new(&bindings);
+        if let Some(path) = camera_path {
+            tasks.active_mut(&initial_state).play_path(path.clone(), initial_state.camera());
+            user_keys.camera_path = Some(path);
+        }
+        let capture_every = DESIRED_CPS / CAPTURE_FPS;
+        let mut capture_step = 0;
 
         let mut seer = Seer::new(initial_state.clone(), tasks.clone());
         let mut seer_apprentice = None;
@@ -33,35 +60,57 @@ pub fn start(initial_state: State, events: EventsLoop) -> Receiver<State> {
         loop {
             let it_start = time::precise_time_s();
             let mut delta = it_start - last_loop;
+            if capture_acks.is_some() {
+                delta = DESIRED_DELTA; // fixed steps for reproducible captures
+            }
+            let input_delta = delta;
             last_loop = it_start;
             if state.pause {
                 delta = 0.0;
             }
+            tasks.clock.advance(input_delta);
 
             events.poll_events(|Event::WindowEvent{ event, .. }| {
                 match event {
-                    WindowEvent::KeyboardInput(_, _, Some(VirtualKeyCode::Escape), _) |
                     WindowEvent::Closed => state.user_quit = true,
-                    _ => {}
+                    _ => if bindings.event_action(&event) == Some((Action::Quit, true)) {
+                        state.user_quit = true;
+                    }
                 }
-                user_mouse.handle(&mut state, delta as f32, &event, &mut tasks);
-                user_keys.handle(&mut state, delta as f32, &event, &mut tasks);
+                user_mouse.handle(&mut state, &event, &mut tasks);
+                user_keys.handle(&mut state, &event, &mut tasks);
             });
+            user_keys.update(&mut state, input_delta as f32, &tasks);
+            user_mouse.update(&mut state, input_delta as f32, &tasks);
+            for playback in tasks.cameras.iter_mut().filter_map(|c| c.path.as_mut()) {
+                playback.advance(input_delta as f32);
+            }
 
-
-            compute_state(&mut state, &mut tasks, delta);
-            trace!("compute_state in {:.3}s", time::precise_time_s() - it_start);
+            let simulate = !state.pause && !benchmark;
+            let compute_start = time::precise_time_s();
+            if simulate {
+                compute_state(&mut state, &mut tasks, delta);
+            }
+            else {
+                // bodies are still, only the cameras move
+                tasks.update(&mut state);
+            }
+            let compute_end = time::precise_time_s();
+            trace!("compute_state in {:.3}s", compute_end - it_start);
 
             handle_seer_projections(&mut state, &mut seer);
-            trace!("handle_seer_projections in {:.3}s", time::precise_time_s() - it_start);
-
-            if seer_apprentice.is_none() {
-                let mut zoom = tasks.zoom.as_ref()
-                    .map(|z| z.zoom_destination())
-                    .unwrap_or(state.zoom);
-                if zoom > state.zoom {
-                    zoom = state.zoom;
-                }
+            let seer_end = time::precise_time_s();
+            trace!("handle_seer_projections in {:.3}s", seer_end - it_start);
+
+            let corotating = tasks.corotating(&state);
+            if seer.corotating != corotating {
+                // curves in a different frame are useless, so replace the seer right away
+                debug!("Reference frame change, getting a new seer...");
+                seer = Seer::new(state.clone(), tasks.clone());
+                seer_apprentice = None;
+            }
+            else if seer_apprentice.is_none() {
+                let zoom = tasks.nearest_zoom(&state);
                 // if we can tell the seer is losing his touch, ie his curves start erroneously
                 // far from the orbit bodies, we spin up an apprentice in parallel seeded with
                 // newer state. When the apprentice as 99% of the plots of his master we switch
@@ -92,9 +141,11 @@ pub fn start(initial_state: State, events: EventsLoop) -> Receiver<State> {
                 }
             }
 
-            seer.main_deltas.send(delta).expect("seer->delta");
-            if let Some(ref apprentice) = seer_apprentice {
-                apprentice.main_deltas.send(delta).expect("apprentice seer->delta");
+            if simulate {
+                seer.main_deltas.send(delta).expect("seer->delta");
+                if let Some(ref apprentice) = seer_apprentice {
+                    apprentice.main_deltas.send(delta).expect("apprentice seer->delta");
+                }
             }
 
             delta_sum += delta;
@@ -105,15 +156,43 @@ pub fn start(initial_state: State, events: EventsLoop) -> Receiver<State> {
                 delta_count = 0;
             }
             state.debug_info.mean_cps = mean_cps;
+            state.debug_info.body_count = state.drawables.orbit_bodies.len();
+            state.debug_info.seer_plots = state.drawables.orbit_curves.first()
+                .map(|curve| curve.plots.len())
+                .unwrap_or(0);
+            state.debug_info.min_plot_distance = seer.min_plot_distance;
+            state.debug_info.apprentice_training = seer_apprentice.is_some();
+            state.debug_info.timings = StepTimings {
+                compute_state: compute_end - compute_start,
+                seer: seer_end - compute_end,
+                total: time::precise_time_s() - it_start,
+            };
+            state.debug_info.total_step_time += state.debug_info.timings.total;
+
+            state.capture_frame = None;
+            if capture_acks.is_some() {
+                if tasks.cameras.iter().all(|c| c.path.is_none()) {
+                    info!("Capture finished");
+                    state.user_quit = true;
+                }
+                else if capture_step % capture_every == 0 {
+                    state.capture_frame = Some(capture_step / capture_every + 1);
+                }
+                capture_step += 1;
+            }
 
             // update render state
             if render_state.update(state.clone()).is_err() {
                 break; // rendering has finished / no getter
             }
+            if let (Some(frame), Some(acks)) = (state.capture_frame, capture_acks.as_ref()) {
+                // wait for the frame to be saved, so no frames are skipped however slow rendering is
+                while acks.recv().map(|saved| saved < frame).unwrap_or(false) {}
+            }
             trace!("render_state.update in {:.3}s", time::precise_time_s() - it_start);
 
             let sleep_delta = DESIRED_DELTA - (time::precise_time_s() - it_start);
-            if sleep_delta > 0.0 {
+            if sleep_delta > 0.0 && capture_acks.is_none() {
                 thread::sleep(Duration::new(0, (sleep_delta * 1_000_000_000.0) as u32));
             }
         }
@@ -122,6 +201,20 @@ pub fn start(initial_state: State, events: EventsLoop) -> Receiver<State> {
     latest_state_getter
 }
 
+/// Gravitational acceleration of `body` toward `other`
+pub fn pair_acceleration(body: &OrbitBody, other: &OrbitBody) -> Vector2<f64> {
+    let dist_squared = body.center.distance2(other.center);
+    let acceleration_scalar = GRAVITY * other.mass / dist_squared;
+    (other.center - body.center).normalize_to(acceleration_scalar)
+}
+
+/// Net gravitational acceleration of the body at `idx` from all other bodies
+pub fn net_acceleration(bodies: &[OrbitBody], idx: usize) -> Vector2<f64> {
+    bodies.iter().enumerate()
+        .filter(|&(idx2, _)| idx2 != idx)
+        .fold(Vector2::zero(), |sum, (_, other)| sum + pair_acceleration(&bodies[idx], other))
+}
+
 fn compute_state_single(mut state: &mut State, tasks: &mut Tasks, delta: f64) {
     for idx in 0..state.drawables.orbit_bodies.len() {
         let mut new_velocity = state.drawables.orbit_bodies[idx].velocity;
@@ -130,11 +223,7 @@ fn compute_state_single(mut state: &mut State, tasks: &mut Tasks, delta: f64) {
             if idx != idx2 {
                 let body = &state.drawables.orbit_bodies[idx];
                 let other = &state.drawables.orbit_bodies[idx2];
-                let dist_squared = body.center.distance2(other.center);
-                let acceleration_scalar = GRAVITY * other.mass / dist_squared;
-                let accelaration = (other.center - body.center).normalize_to(acceleration_scalar);
-
-                new_velocity += delta * accelaration;
+                new_velocity += delta * pair_acceleration(body, other);
             }
         }
 
@@ -153,12 +242,7 @@ fn compute_state_par(mut state: &mut State, tasks: &mut Tasks, delta: f64) {
     state.drawables.orbit_bodies.par_iter_mut().for_each(|body| {
         body.velocity += bodies.par_iter()
             .filter(|other| other.id != body.id)
-            .map(|other| {
-                let dist_squared = body.center.distance2(other.center);
-                let acceleration_scalar = GRAVITY * other.mass / dist_squared;
-                let accelaration = (other.center - body.center).normalize_to(acceleration_scalar);
-                delta * accelaration
-            })
+            .map(|other| delta * pair_acceleration(body, other))
             .sum();
     });
 
@@ -170,6 +254,7 @@ fn compute_state_par(mut state: &mut State, tasks: &mut Tasks, delta: f64) {
 }
 
 pub fn compute_state(mut state: &mut State, tasks: &mut Tasks, delta: f64) {
+    tasks.clock.advance_sim(delta);
     // benchmarks currently show > 16*4 bodies as the sweet spot for parallel impl
     if state.drawables.orbit_bodies.len() > 64 {
         compute_state_par(state, tasks, delta)
@@ -181,10 +266,12 @@ pub fn compute_state(mut state: &mut State, tasks: &mut Tasks, delta: f64) {
 
 fn handle_seer_projections(state: &mut State, seer: &mut Seer) {
     state.drawables.orbit_curves = seer.projection.latest().clone();
+    state.drawables.curves_corotating = seer.corotating;
 
-    // fade between [10, 20]
-    if state.zoom > 10.0 {
-        let opacity = 1.0 - (state.zoom - 10.0) / 10.0;
+    // fade between [10, 20] of the most zoomed in camera
+    let zoom = state.cameras.iter().map(|c| c.zoom).fold(f32::INFINITY, f32::min);
+    if zoom > 10.0 {
+        let opacity = 1.0 - (zoom - 10.0) / 10.0;
         for curve in &mut state.drawables.orbit_curves {
             curve.opacity = opacity;
         }
@@ -196,11 +283,51 @@ fn handle_seer_projections(state: &mut State, seer: &mut Seer) {
     }
 }
 
+#[cfg(test)]
+mod compute_test {
+    use super::*;
+    use orbitbody::*;
+
+    fn body(x: f64, mass: f64) -> OrbitBody {
+        OrbitBody {
+            color: palette_color(0),
+            ..OrbitBody::new((x, 0.0).into(), (0.0, 0.0).into(), mass, 0.5)
+        }
+    }
+
+    #[test]
+    fn net_acceleration_matches_stepped_velocity() {
+        let bodies = vec!(body(0.0, 1000.0), body(10.0, 10.0), body(-5.0, 1.0));
+        let accel = net_acceleration(&bodies, 1);
+        assert!((accel.x - (-GRAVITY * 1000.0 / 100.0 - GRAVITY * 1.0 / 225.0)).abs() < 1e-12);
+        assert_eq!(accel.y, 0.0);
+
+        let mut state = State::new(100, 100);
+        state.drawables.orbit_bodies = bodies;
+        compute_state(&mut state, &mut Tasks::new(), 0.001);
+        assert!((state.drawables.orbit_bodies[1].velocity - accel * 0.001).magnitude() < 1e-12);
+    }
+
+    #[test]
+    fn simulation_time_follows_compute_steps() {
+        let mut state = State::new(100, 100);
+        let mut tasks = Tasks::with_clock(Clock::manual());
+        compute_state(&mut state, &mut tasks, 0.25);
+        compute_state(&mut state, &mut tasks, 0.5);
+        assert_eq!(tasks.clock.sim(), 0.75);
+
+        // paused, so only the cameras & wall time move
+        tasks.clock.advance(1.0);
+        tasks.update(&mut state);
+        assert_eq!(tasks.clock.sim(), 0.75);
+        assert_eq!(tasks.clock.wall(), 1.0);
+    }
+}
+
 #[cfg(feature = "bench")]
 #[cfg(test)]
 mod compute_bench {
     use super::*;
-    use uuid::Uuid;
     use orbitbody::*;
     use rayon;
 
@@ -210,35 +337,24 @@ mod compute_bench {
         Drawables {
             orbit_bodies: vec!(
                 OrbitBody {
-                    id: Uuid::new_v4(),
-                    center: (0.0, 0.0).into(),
-                    radius: 1.2,
-                    mass: 1660.0,
-                    velocity: (0.0, -1.0).into(),
+                    color: palette_color(0),
+                    ..OrbitBody::new((0.0, 0.0).into(), (0.0, -1.0).into(), 1660.0, 1.2)
                 },
                 OrbitBody {
-                    id: Uuid::new_v4(),
-                    center: (3.5, 0.0).into(),
-                    radius: 0.9,
-                    mass: 1000.0,
-                    velocity: (0.0, 1.6).into(),
+                    color: palette_color(1),
+                    ..OrbitBody::new((3.5, 0.0).into(), (0.0, 1.6).into(), 1000.0, 0.9)
                 },
                 OrbitBody {
-                    id: Uuid::new_v4(),
-                    center: (9.0, 0.0).into(),
-                    radius: 0.3,
-                    mass: 1.0,
-                    velocity: (0.0, 2.0).into(),
+                    color: palette_color(2),
+                    ..OrbitBody::new((9.0, 0.0).into(), (0.0, 2.0).into(), 1.0, 0.3)
                 },
                 OrbitBody {
-                    id: Uuid::new_v4(),
-                    center: (-12.0, 0.0).into(),
-                    radius: 0.4,
-                    mass: 2.0,
-                    velocity: (0.0, -1.5).into(),
+                    color: palette_color(3),
+                    ..OrbitBody::new((-12.0, 0.0).into(), (0.0, -1.5).into(), 2.0, 0.4)
                 },
             ),
             orbit_curves: vec!(),
+            curves_corotating: None,
         }
     }
 
@@ -247,6 +363,7 @@ mod compute_bench {
         state.drawables = Drawables {
             orbit_bodies: vec!(),
             orbit_curves: vec!(),
+            curves_corotating: None,
         };
         // 100x few bodies load
         for i in 0..load {
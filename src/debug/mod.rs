@@ -1,25 +1,167 @@
 pub mod render;
 
+use std::collections::VecDeque;
+
+/// Samples kept by time graphs
+pub const GRAPH_SAMPLES: usize = 240;
+
+/// Rolling window of recent times in seconds, ie frame times
+#[derive(Clone, Debug)]
+pub struct TimeGraph {
+    samples: VecDeque<f32>,
+}
+
+impl TimeGraph {
+    pub fn new() -> TimeGraph {
+        TimeGraph { samples: VecDeque::with_capacity(GRAPH_SAMPLES) }
+    }
+
+    pub fn push(&mut self, seconds: f64) {
+        if self.samples.len() == GRAPH_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(seconds as f32);
+    }
+
+    /// Samples oldest first
+    pub fn samples(&self) -> &VecDeque<f32> {
+        &self.samples
+    }
+
+    pub fn max(&self) -> f32 {
+        self.samples.iter().cloned().fold(0.0, f32::max)
+    }
+}
+
+impl Default for TimeGraph {
+    fn default() -> TimeGraph {
+        TimeGraph::new()
+    }
+}
+
+/// Seconds taken by the parts of a compute step
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StepTimings {
+    pub compute_state: f64,
+    pub seer: f64,
+    /// whole step, up to handing state to rendering
+    pub total: f64,
+}
+
 #[derive(Clone, Debug)]
 pub struct ComputeDebugInfo {
     pub mean_cps: u32,
+    pub body_count: usize,
+    /// plots the seer has computed ahead
+    pub seer_plots: usize,
+    pub min_plot_distance: f64,
+    /// an apprentice seer is training to replace the seer
+    pub apprentice_training: bool,
+    pub timings: StepTimings,
+    /// seconds spent in every compute step so far, the difference between rendered frames
+    /// is graphed as the step time of a frame
+    pub total_step_time: f64,
 }
 
+/// Everything shown by the debug HUD
 #[derive(Clone, Debug)]
 pub struct DebugInfo<'a> {
     pub compute: &'a ComputeDebugInfo,
-    pub mean_fps: u32
+    pub mean_fps: u32,
+    /// name of the body the camera is following
+    pub following: Option<String>,
+    pub frame_times: &'a TimeGraph,
+    /// compute step time per rendered frame
+    pub step_times: &'a TimeGraph,
+    /// vertices drawn for curves & trails last frame
+    pub curve_vertices: usize,
+    /// show the full HUD, otherwise just fps & cps
+    pub expanded: bool,
 }
 
 impl ComputeDebugInfo {
     pub fn initial() -> ComputeDebugInfo {
-        ComputeDebugInfo { mean_cps: 0 }
+        ComputeDebugInfo {
+            mean_cps: 0,
+            body_count: 0,
+            seer_plots: 0,
+            min_plot_distance: 0.0,
+            apprentice_training: false,
+            timings: StepTimings::default(),
+            total_step_time: 0.0,
+        }
     }
+}
 
-    pub fn add_render_info(&self, mean_fps: u32) -> DebugInfo {
-        DebugInfo {
-            mean_fps,
-            compute: self,
+impl<'a> DebugInfo<'a> {
+    /// HUD text lines
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec!(format!("{} fps, {} cps", self.mean_fps, self.compute.mean_cps));
+        if let Some(ref name) = self.following {
+            lines.push(format!("following {}", name));
         }
+        if !self.expanded {
+            return lines;
+        }
+        let compute = self.compute;
+        let ms = |seconds: f64| seconds * 1000.0;
+        lines.push(format!("bodies {}", compute.body_count));
+        lines.push(format!("seer plots {}, min plot distance {}{}",
+                           compute.seer_plots,
+                           compute.min_plot_distance,
+                           if compute.apprentice_training { ", apprentice training" } else { "" }));
+        lines.push(format!("curve vertices {}", self.curve_vertices));
+        lines.push(format!("step {:.2}ms: compute {:.2}ms, seer {:.2}ms",
+                           ms(compute.timings.total),
+                           ms(compute.timings.compute_state),
+                           ms(compute.timings.seer)));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod debug_test {
+    use super::*;
+
+    #[test]
+    fn time_graph_rolls() {
+        let mut graph = TimeGraph::new();
+        for n in 0..GRAPH_SAMPLES + 10 {
+            graph.push(n as f64);
+        }
+        assert_eq!(graph.samples().len(), GRAPH_SAMPLES);
+        assert_eq!(graph.samples()[0], 10.0);
+        assert_eq!(graph.max(), (GRAPH_SAMPLES + 9) as f32);
+    }
+
+    #[test]
+    fn hud_lines() {
+        let mut compute = ComputeDebugInfo::initial();
+        compute.mean_cps = 1000;
+        compute.body_count = 4;
+        compute.seer_plots = 1200;
+        compute.min_plot_distance = 0.27;
+        compute.apprentice_training = true;
+        compute.timings = StepTimings { compute_state: 0.0005, seer: 0.00025, total: 0.001 };
+        let frame_times = TimeGraph::new();
+        let step_times = TimeGraph::new();
+        let mut info = DebugInfo {
+            compute: &compute,
+            mean_fps: 60,
+            following: Some("Io".into()),
+            frame_times: &frame_times,
+            step_times: &step_times,
+            curve_vertices: 300,
+            expanded: false,
+        };
+        assert_eq!(info.lines(), vec!("60 fps, 1000 cps", "following Io"));
+
+        info.expanded = true;
+        assert_eq!(&info.lines()[2..], &[
+            "bodies 4",
+            "seer plots 1200, min plot distance 0.27, apprentice training",
+            "curve vertices 300",
+            "step 1.00ms: compute 0.50ms, seer 0.25ms",
+        ]);
     }
 }
@@ -1,25 +1,128 @@
-use super::DebugInfo;
+use super::*;
+use super::super::{ColorFormat, DepthFormat};
+use lines::render::{LineBrush, LineVertex, screen_transform};
 use gfx::*;
 use gfx_text::{HorizontalAnchor, VerticalAnchor};
 use gfx_text;
 
+const TEXT_COLOR: [f32; 4] = [0.3, 0.6, 0.8, 1.0];
+const LINE_PIXELS: i32 = 16;
+const FRAME_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 0.9];
+const STEP_COLOR: [f32; 4] = [1.0, 0.6, 0.2, 0.9];
+const BOX_COLOR: [f32; 4] = [0.3, 0.6, 0.8, 0.6];
+const REFERENCE_COLOR: [f32; 4] = [0.3, 0.6, 0.8, 0.3];
+/// graph reference line, a 60fps frame
+const REFERENCE_TIME: f32 = 1.0 / 60.0;
+const GRAPH_HEIGHT_PIXELS: f32 = 60.0;
+/// in front of everything
+const GRAPH_DEPTH: f32 = -1.0;
+
+/// Line list vertices of the time graph with its top left at `top` pixels, on a screen
+/// `width` x `height` pixels. Times are scaled so twice the reference time, or the slowest sample,
+/// fits the graph
+fn graph_vertices(info: &DebugInfo, top: (f32, f32), (width, height): (f32, f32)) -> Vec<LineVertex> {
+    let (left, top) = top;
+    let graph_width = GRAPH_SAMPLES as f32;
+    let bottom = top + GRAPH_HEIGHT_PIXELS;
+    let max_time = (2.0 * REFERENCE_TIME)
+        .max(info.frame_times.max())
+        .max(info.step_times.max());
+    let ndc = |x: f32, y: f32| [x / width * 2.0 - 1.0, 1.0 - y / height * 2.0];
+    let time_y = |time: f32| bottom - time / max_time * GRAPH_HEIGHT_PIXELS;
+
+    let mut verts = Vec::new();
+    let mut line = |(x1, y1), (x2, y2), color| {
+        verts.push(LineVertex { position: ndc(x1, y1), color });
+        verts.push(LineVertex { position: ndc(x2, y2), color });
+    };
+    let right = left + graph_width;
+    line((left, top), (right, top), BOX_COLOR);
+    line((right, top), (right, bottom), BOX_COLOR);
+    line((right, bottom), (left, bottom), BOX_COLOR);
+    line((left, bottom), (left, top), BOX_COLOR);
+    let reference = time_y(REFERENCE_TIME);
+    line((left, reference), (right, reference), REFERENCE_COLOR);
+
+    for &(graph, color) in &[(info.frame_times, FRAME_COLOR), (info.step_times, STEP_COLOR)] {
+        // newest sample on the right
+        let offset = right - graph.samples().len() as f32;
+        let points: Vec<_> = graph.samples().iter()
+            .enumerate()
+            .map(|(idx, time)| (offset + idx as f32, time_y(*time)))
+            .collect();
+        for pair in points.windows(2) {
+            line(pair[0], pair[1], color);
+        }
+    }
+    verts
+}
+
 pub struct DebugInfoBrush<R: Resources, F: Factory<R>> {
-    renderer: gfx_text::Renderer<R, F>
+    renderer: gfx_text::Renderer<R, F>,
+    graph: LineBrush<R, F>,
+    target: handle::RenderTargetView<R, ColorFormat>,
 }
 
 impl<R: Resources, F: Factory<R> + Clone> DebugInfoBrush<R, F> {
-    pub fn new(factory: &F) -> DebugInfoBrush<R, F> {
+    pub fn new(factory: F,
+               target: &handle::RenderTargetView<R, ColorFormat>,
+               depth_target: &handle::DepthStencilView<R, DepthFormat>)
+               -> DebugInfoBrush<R, F>
+    {
         let renderer = gfx_text::new(factory.clone()).with_size(14).unwrap();
-        DebugInfoBrush { renderer }
+        let graph = LineBrush::new(factory, target, depth_target, GRAPH_DEPTH);
+        DebugInfoBrush { renderer, graph, target: target.clone() }
     }
 
-    pub fn draw<C: CommandBuffer<R>, T: format::RenderFormat>(&mut self, encoder: &mut Encoder<R, C>, target: &handle::RenderTargetView<R, T>, info: &DebugInfo)
+    pub fn draw<C: CommandBuffer<R>>(&mut self, encoder: &mut Encoder<R, C>, info: &DebugInfo)
                       -> Result<(), gfx_text::Error>
     {
-        let txt = format!("{} fps, {} cps", info.mean_fps, info.compute.mean_cps);
-        self.renderer.add_anchored(&txt, [5, 5],
-                       HorizontalAnchor::Left, VerticalAnchor::Top,
-                       [0.3, 0.6, 0.8, 1.0]);
-        self.renderer.draw(encoder, target)
+        let lines = info.lines();
+        for (idx, line) in lines.iter().enumerate() {
+            self.renderer.add_anchored(line, [5, 5 + LINE_PIXELS * idx as i32],
+                           HorizontalAnchor::Left, VerticalAnchor::Top,
+                           TEXT_COLOR);
+        }
+        self.renderer.draw(encoder, &self.target)?;
+
+        if info.expanded {
+            let (width, height, ..) = self.target.get_dimensions();
+            let top = (5 + LINE_PIXELS * lines.len() as i32 + 4) as f32;
+            let verts = graph_vertices(info, (5.0, top), (f32::from(width), f32::from(height)));
+            self.graph.draw_lines(encoder, &screen_transform(), &verts);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+
+    #[test]
+    fn graph_scaled_to_slowest_sample() {
+        let compute = ComputeDebugInfo::initial();
+        let mut step_times = TimeGraph::new();
+        step_times.push(0.001);
+        let mut frame_times = TimeGraph::new();
+        frame_times.push(0.1);
+        frame_times.push(0.05);
+        let info = DebugInfo {
+            compute: &compute,
+            mean_fps: 10,
+            following: None,
+            frame_times: &frame_times,
+            step_times: &step_times,
+            curve_vertices: 0,
+            expanded: true,
+        };
+        let verts = graph_vertices(&info, (0.0, 0.0), (GRAPH_SAMPLES as f32, GRAPH_HEIGHT_PIXELS));
+
+        // box, reference & a single frame time segment
+        assert_eq!(verts.len(), (4 + 1 + 1) * 2);
+        let frame_segment = &verts[10..];
+        // slowest sample at the top, ending at the right edge
+        assert_eq!(frame_segment[0].position, [1.0 - 2.0 * 2.0 / GRAPH_SAMPLES as f32, 1.0]);
+        assert_eq!(frame_segment[1].position, [1.0 - 2.0 / GRAPH_SAMPLES as f32, 0.0]);
     }
 }
@@ -1,4 +1,3 @@
-
 #[derive(Clone, Debug)]
 pub struct Easer<T> {
     /// a floating point time unit, ie time::precise_time_s()
@@ -9,15 +8,91 @@ pub struct Easer<T> {
     pub transitions: Vec<(T, T)>,
     /// easing function fn(t,b,c,d) -> float
     easing: fn(T, T, T, T) -> T,
+    /// per transition corrections keeping values continuous across mid-flight changes
+    blends: Vec<Option<Blend<T>>>,
 }
 
 use num::{Float, Zero};
+use std::u64;
 
-#[allow(dead_code)]
 fn linear_easing<F: Float>(t: F, b: F, c: F, d: F) -> F {
     c * t / d + b
 }
 
+/// Value offset blended out over the remaining transition progress, so both offset & slope
+/// are zero at the end.
+///
+/// `offset(s) = h00(u) * (value + eased_slope * (e(s) - e(from))) + h10(u) * span * slope`
+/// with cubic hermite basis functions of `u`, the remaining progress fraction, & the normalised
+/// easing function `e`. Changes in slope are carried by `eased_slope` where possible, so the
+/// offset decays with the same profile as the easing & doesn't overshoot.
+#[derive(Clone, Copy, Debug)]
+struct Blend<F> {
+    /// progress in [0, 1) the blend starts from
+    from: F,
+    /// normalised easing value at #from
+    ease_from: F,
+    /// offset at #from
+    value: F,
+    /// rate of change of the offset per unit normalised easing value at #from
+    eased_slope: F,
+    /// rate of change of the offset per unit progress at #from, not following the easing
+    slope: F,
+}
+
+impl<F: Float> Blend<F> {
+    /// Returns a blend with an offset value & slope, per unit progress, at a progress
+    fn starting<E: Fn(F) -> F>(from: F, value: F, slope: F, ease: E) -> Blend<F> {
+        let ease_slope = progress_slope(&ease, from);
+        let (eased_slope, slope) = if ease_slope.abs() > F::from(0.001).unwrap() {
+            (slope / ease_slope, F::zero())
+        }
+        else {
+            (F::zero(), slope)
+        };
+        Blend { from, ease_from: ease(from), value, eased_slope, slope }
+    }
+
+    /// Returns (offset, offset slope per unit progress) at a progress in [#from, 1]
+    fn at<E: Fn(F) -> F>(&self, progress: F, ease: E) -> (F, F) {
+        let (one, two, three) = (F::one(), F::from(2).unwrap(), F::from(3).unwrap());
+        let span = one - self.from;
+        let u = ((progress - self.from) / span).max(F::zero()).min(one);
+        let (u2, u3) = (u * u, u * u * u);
+
+        let h00 = two * u3 - three * u2 + one;
+        let h10 = u3 - two * u2 + u;
+        let dh00 = F::from(6).unwrap() * (u2 - u);
+        let dh10 = three * u2 - F::from(4).unwrap() * u + one;
+
+        let eased = self.value + self.eased_slope * (ease(progress) - self.ease_from);
+        let eased_slope = self.eased_slope * progress_slope(&ease, progress);
+        (h00 * eased + self.slope * span * h10,
+         dh00 / span * eased + h00 * eased_slope + self.slope * dh10)
+    }
+}
+
+/// Easing function from 0 to 1 over progress in [0, 1]
+fn normalised<F: Float>(easing: fn(F, F, F, F) -> F, progress: F) -> F {
+    easing(progress, F::zero(), F::one(), F::one())
+}
+
+/// Rate of change of a normalised easing function per unit progress
+fn progress_slope<F: Float, E: Fn(F) -> F>(ease: E, progress: F) -> F {
+    let h = F::from(0.0001).unwrap();
+    let one = F::one();
+    let ease = |p: F| ease(p.max(F::zero()).min(one));
+    if progress - h < F::zero() {
+        (ease(progress + h) - ease(progress)) / h
+    }
+    else if progress + h > one {
+        (ease(progress) - ease(progress - h)) / h
+    }
+    else {
+        (ease(progress + h) - ease(progress - h)) / (h + h)
+    }
+}
+
 impl<F: Float + Zero> Easer<F> {
     pub fn using(easing: fn(F, F, F, F) -> F) -> Easer<F> {
         Easer {
@@ -25,6 +100,7 @@ impl<F: Float + Zero> Easer<F> {
             duration: F::zero(),
             transitions: Vec::new(),
             easing: easing,
+            blends: Vec::new(),
         }
     }
 
@@ -45,9 +121,69 @@ impl<F: Float + Zero> Easer<F> {
 
     pub fn add_transition<T: Into<F>, V: Into<F>>(mut self, from: T, to: V) -> Easer<F> {
         self.transitions.push((from.into(), to.into()));
+        self.blends.push(None);
         self
     }
 
+    /// Starts each transition at a velocity, per time unit, instead of the easing function's
+    /// own initial velocity. Use after setting #duration & adding transitions.
+    pub fn initial_velocities(mut self, velocities: &[F]) -> Easer<F> {
+        let d = self.duration;
+        let easing = self.easing;
+        let ease = |progress| normalised(easing, progress);
+        for (idx, velocity) in velocities.iter().enumerate().take(self.transitions.len()) {
+            let (from, to) = self.transitions[idx];
+            let eased_slope = (to - from) * progress_slope(ease, F::zero());
+            self.blends[idx] = Some(Blend::starting(F::zero(), F::zero(), *velocity * d - eased_slope,
+                                                    &ease));
+        }
+        self
+    }
+
+    /// Changes the destination of a transition mid-flight. The change is blended in over the
+    /// remaining duration, so the value & velocity at `time` are unchanged.
+    pub fn retarget<T: Into<F>, V: Into<F>>(&mut self, idx: usize, time: T, to: V) {
+        let (time, to) = (time.into(), to.into());
+        let t = time - self.start;
+        let d = self.duration;
+        let old_to = self.transitions[idx].1;
+        self.transitions[idx].1 = to;
+        if t >= d || d <= F::zero() {
+            self.blends[idx] = None;
+            return;
+        }
+
+        let progress = (t / d).max(F::zero());
+        if F::one() - progress < F::from(0.001).unwrap() {
+            // too little of the transition left to blend over, snap to the new destination
+            self.blends[idx] = None;
+            return;
+        }
+        let easing = self.easing;
+        let ease = |progress| normalised(easing, progress);
+        let (offset, offset_slope) = self.blends[idx]
+            .map(|blend| blend.at(progress, &ease))
+            .unwrap_or((F::zero(), F::zero()));
+        // the eased part of the value changes with the destination, offset that change
+        let eased_change = old_to - to;
+        self.blends[idx] = Some(Blend::starting(
+            progress,
+            offset + eased_change * ease(progress),
+            offset_slope + eased_change * progress_slope(ease, progress),
+            &ease));
+    }
+
+    /// Value of a transition at time `t` from start, inside the duration
+    fn value_in_flight(&self, idx: usize, t: F) -> F {
+        let (b, to) = self.transitions[idx];
+        let d = self.duration;
+        let eased = (self.easing)(t, b, to - b, d);
+        match self.blends[idx] {
+            Some(blend) => eased + blend.at(t / d, |progress| normalised(self.easing, progress)).0,
+            None => eased,
+        }
+    }
+
     fn out_of_bound_values_at(&self, time: F) -> Option<Vec<F>> {
         let delta = time - self.start;
         if delta <= F::zero() {
@@ -65,13 +201,27 @@ impl<F: Float + Zero> Easer<F> {
             return vals;
         }
         let t = time - self.start;
+        (0..self.transitions.len())
+            .map(|idx| self.value_in_flight(idx, t))
+            .collect()
+    }
+
+    /// Returns the rate of change of each transition value per time unit
+    pub fn velocities_at<T: Into<F>>(&self, time: T) -> Vec<F> {
+        let time: F = time.into();
+        let t = time - self.start;
         let d = self.duration;
-        self.transitions.iter()
-            .map(|transition| {
-                let b = transition.0;
-                let c = transition.1 - transition.0;
-                (self.easing)(t, b, c, d)
-            })
+        if t < F::zero() || t >= d {
+            return self.transitions.iter().map(|_| F::zero()).collect();
+        }
+        // numeric differentiation in the easing function's own time, so unaffected by
+        // the precision of large absolute times
+        let h = d / F::from(10_000).unwrap();
+        let (t1, t2) = if t - h < F::zero() { (t, t + h) }
+            else if t + h > d { (t - h, t) }
+            else { (t - h, t + h) };
+        (0..self.transitions.len())
+            .map(|idx| (self.value_in_flight(idx, t2) - self.value_in_flight(idx, t1)) / (t2 - t1))
             .collect()
     }
 
@@ -81,6 +231,154 @@ impl<F: Float + Zero> Easer<F> {
     }
 }
 
+/// One eased step of an `EaseTimeline`, from the previous segment's values
+#[derive(Clone, Debug)]
+struct Segment<F> {
+    duration: F,
+    to: Vec<F>,
+    easing: fn(F, F, F, F) -> F,
+}
+
+/// Chain of eased segments over a set of values, with optional start delay, holds,
+/// ping-pong & repeat
+#[derive(Clone, Debug)]
+pub struct EaseTimeline<F> {
+    /// a floating point time unit, ie time::precise_time_s()
+    pub start: F,
+    /// time before the first play starts, not repeated
+    pub delay: F,
+    /// values before the first segment
+    initial: Vec<F>,
+    segments: Vec<Segment<F>>,
+    /// every other play runs backwards
+    ping_pong: bool,
+    /// total number of plays, `None` for forever
+    plays: Option<u32>,
+}
+
+impl<F: Float + Zero> EaseTimeline<F> {
+    pub fn from_values(initial: &[F]) -> EaseTimeline<F> {
+        EaseTimeline {
+            start: F::zero(),
+            delay: F::zero(),
+            initial: initial.to_vec(),
+            segments: Vec::new(),
+            ping_pong: false,
+            plays: Some(1),
+        }
+    }
+
+    pub fn start<T: Into<F>>(mut self, start: T) -> EaseTimeline<F> {
+        self.start = start.into();
+        self
+    }
+
+    pub fn delay<T: Into<F>>(mut self, delay: T) -> EaseTimeline<F> {
+        self.delay = delay.into();
+        self
+    }
+
+    /// Adds a segment easing from the current end values to `to`
+    pub fn then<T: Into<F>>(mut self, easing: fn(F, F, F, F) -> F, duration: T, to: &[F]) -> EaseTimeline<F> {
+        assert_eq!(to.len(), self.initial.len(), "segment values must match the initial values");
+        self.segments.push(Segment { duration: duration.into(), to: to.to_vec(), easing });
+        self
+    }
+
+    /// Adds a segment keeping the current end values
+    pub fn hold<T: Into<F>>(self, duration: T) -> EaseTimeline<F> {
+        let values = self.end_values().to_vec();
+        self.then(linear_easing, duration, &values)
+    }
+
+    /// Plays forwards then backwards, each direction counting as a play
+    pub fn ping_pong(mut self) -> EaseTimeline<F> {
+        self.ping_pong = true;
+        self
+    }
+
+    /// Plays the segments `times` more after the first play
+    pub fn repeat(mut self, times: u32) -> EaseTimeline<F> {
+        self.plays = Some(times.saturating_add(1));
+        self
+    }
+
+    pub fn repeat_forever(mut self) -> EaseTimeline<F> {
+        self.plays = None;
+        self
+    }
+
+    /// Time taken to play all segments once
+    pub fn play_duration(&self) -> F {
+        self.segments.iter().fold(F::zero(), |sum, s| sum + s.duration)
+    }
+
+    /// Total time from start until finished, including the delay, `None` if repeating forever
+    pub fn duration(&self) -> Option<F> {
+        self.plays.map(|plays| self.delay + self.play_duration() * F::from(plays).unwrap())
+    }
+
+    fn end_values(&self) -> &[F] {
+        self.segments.last().map(|s| &s.to[..]).unwrap_or(&self.initial)
+    }
+
+    /// Values after the final play
+    fn final_values(&self, plays: u32) -> Vec<F> {
+        if self.ping_pong && plays & 1 == 0 {
+            self.initial.clone()
+        }
+        else {
+            self.end_values().to_vec()
+        }
+    }
+
+    /// Values at time `t` into a forwards play
+    fn play_values_at(&self, mut t: F) -> Vec<F> {
+        let mut from = &self.initial;
+        for segment in &self.segments {
+            if t < segment.duration {
+                return from.iter().zip(&segment.to)
+                    .map(|(&b, &to)| (segment.easing)(t, b, to - b, segment.duration))
+                    .collect();
+            }
+            t = t - segment.duration;
+            from = &segment.to;
+        }
+        from.clone()
+    }
+
+    pub fn values_at<T: Into<F>>(&self, time: T) -> Vec<F> {
+        let t = time.into() - self.start - self.delay;
+        if t <= F::zero() {
+            return self.initial.clone();
+        }
+        let play_duration = self.play_duration();
+        if play_duration <= F::zero() {
+            return self.final_values(self.plays.unwrap_or(1));
+        }
+
+        let play = (t / play_duration).floor();
+        let play_number = play.to_u64().unwrap_or(u64::MAX);
+        if let Some(plays) = self.plays {
+            if play_number >= u64::from(plays) {
+                return self.final_values(plays);
+            }
+        }
+        let play_t = t - play * play_duration;
+        if self.ping_pong && play_number & 1 == 1 {
+            self.play_values_at(play_duration - play_t)
+        }
+        else {
+            self.play_values_at(play_t)
+        }
+    }
+
+    pub fn has_finished<T: Into<F>>(&self, time: T) -> bool {
+        let time: F = time.into();
+        self.duration().map(|d| time > self.start + d).unwrap_or(false)
+    }
+}
+
 #[cfg(test)]
 mod ease_test {
     use super::*;
@@ -166,4 +464,169 @@ mod ease_test {
 
         check!(cubic_in; f64);
     }
+
+    #[test]
+    fn velocities() {
+        let easer = Easer::<f64>::linear()
+            .duration(2.0)
+            .start(TEST_START)
+            .add_transition(1.0, 5.0)
+            .add_transition(3.0, 3.0);
+
+        let velocities = easer.velocities_at(TEST_START as f64 + 1.0);
+        assert!((velocities[0] - 2.0).abs() < 0.000001, "{:?}", velocities);
+        assert!(velocities[1].abs() < 0.000001, "{:?}", velocities);
+        assert_eq!(easer.velocities_at(TEST_START as f64 + 3.0), vec!(0.0, 0.0));
+
+        let expo = Easer::<f64>::using(Expo::ease_out)
+            .duration(1.0)
+            .add_transition(0.0, 1.0);
+        // d/dt (-2^(-10t) + 1) at 0 is 10ln2
+        assert!((expo.velocities_at(0.0)[0] - 10.0 * 2f64.ln()).abs() < 0.03);
+    }
+
+    macro_rules! check_retarget_continuity {
+        ($type:ty) => {
+            let mut easer = Easer::<$type>::using(Expo::ease_out)
+                .duration(2.0)
+                .start(10.0)
+                .add_transition(0.0, 10.0);
+            let small = 0.001 as $type;
+
+            for &(time, to) in &[(10.3, 4.0), (10.9, 20.0), (11.5, 18.0)] {
+                let (value_before, velocity_before) = (easer.values_at(time)[0], easer.velocities_at(time)[0]);
+                easer.retarget(0, time, to);
+                let (value_after, velocity_after) = (easer.values_at(time)[0], easer.velocities_at(time)[0]);
+                assert!((value_after - value_before).abs() < small,
+                    "value {} -> {} at {}", value_before, value_after, time);
+                assert!((velocity_after - velocity_before).abs() < velocity_before.abs().max(1.0) * 0.01,
+                    "velocity {} -> {} at {}", velocity_before, velocity_after, time);
+            }
+            // expo ease out ends ~0.1% short before snapping to its destination
+            assert!((easer.values_at(12.0 - 0.0001)[0] - 18.0).abs() < 0.05,
+                "Reaches final destination: {}", easer.values_at(12.0 - 0.0001)[0]);
+            assert_eq!(easer.values_at(12.0)[0], 18.0);
+        }
+    }
+
+    #[test]
+    fn retarget_continuous_32() {
+        check_retarget_continuity!(f32);
+    }
+
+    #[test]
+    fn retarget_continuous_64() {
+        check_retarget_continuity!(f64);
+    }
+
+    #[test]
+    fn retarget_from_rest_is_monotonic() {
+        let mut easer = Easer::<f64>::using(Expo::ease_out)
+            .duration(1.0)
+            .add_transition(0.0, 0.0);
+        easer.retarget(0, 0.0, 10.0);
+
+        let values: Vec<f64> = (0..101).map(|i| easer.values_at(i as f64 / 100.0)[0]).collect();
+        assert!(values.windows(2).all(|w| w[1] >= w[0]), "{:?}", values);
+        assert!(values.iter().all(|&v| v <= 10.0), "{:?}", values);
+        assert!(easer.velocities_at(0.0)[0].abs() < 0.01);
+    }
+
+    #[test]
+    fn retarget_after_finish() {
+        let mut easer = Easer::<f64>::linear()
+            .duration(1.0)
+            .add_transition(0.0, 1.0);
+        easer.retarget(0, 2.0, 5.0);
+        assert_eq!(easer.values_at(2.0), vec!(5.0));
+        assert_eq!(easer.velocities_at(2.0), vec!(0.0));
+    }
+
+    #[test]
+    fn retarget_at_the_very_end() {
+        let mut easer = Easer::<f64>::linear()
+            .duration(1.0)
+            .add_transition(0.0, 1.0);
+        easer.retarget(0, 0.99995, 5.0);
+        let value = easer.values_at(0.99999)[0];
+        assert!(value.is_finite() && (value - 5.0).abs() < 0.001, "{}", value);
+    }
+
+    #[test]
+    fn initial_velocities() {
+        let easer = Easer::<f64>::using(Expo::ease_out)
+            .duration(0.5)
+            .add_transition(2.0, 4.0)
+            .add_transition(1.0, 1.0)
+            .initial_velocities(&[-3.0, 7.0]);
+
+        let velocities = easer.velocities_at(0.0);
+        assert!((velocities[0] + 3.0).abs() < 0.01, "{:?}", velocities);
+        assert!((velocities[1] - 7.0).abs() < 0.01, "{:?}", velocities);
+        assert!((easer.values_at(0.0001)[1] - 1.0007).abs() < 0.0001);
+        assert!((easer.values_at(0.4999)[0] - 4.0).abs() < 0.01);
+    }
+
+    macro_rules! check_timeline {
+        ($type:ty) => {
+            let near = |a: Vec<$type>, b: Vec<$type>| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 0.0001);
+
+            let timeline = EaseTimeline::<$type>::from_values(&[0.0, 10.0])
+                .start(100.0)
+                .delay(1.0)
+                .then(Linear::ease_in_out, 2.0, &[4.0, 10.0])
+                .hold(1.0)
+                .then(Quad::ease_in, 1.0, &[4.0, 0.0]);
+
+            assert_eq!(timeline.play_duration(), 4.0);
+            assert_eq!(timeline.duration(), Some(5.0));
+            assert!(near(timeline.values_at(50.0), vec!(0.0, 10.0)), "before start");
+            assert!(near(timeline.values_at(100.5), vec!(0.0, 10.0)), "delayed");
+            assert!(near(timeline.values_at(102.0), vec!(2.0, 10.0)), "linear segment");
+            assert!(near(timeline.values_at(103.5), vec!(4.0, 10.0)), "hold");
+            assert!(near(timeline.values_at(104.5), vec!(4.0, 7.5)), "quad in segment");
+            assert!(near(timeline.values_at(105.0), vec!(4.0, 0.0)), "end");
+            assert!(near(timeline.values_at(200.0), vec!(4.0, 0.0)), "after end");
+            assert!(!timeline.has_finished(104.9));
+            assert!(timeline.has_finished(105.1));
+
+            let ping_pong = EaseTimeline::<$type>::from_values(&[0.0])
+                .then(Linear::ease_in_out, 1.0, &[1.0])
+                .ping_pong()
+                .repeat(2);
+            assert_eq!(ping_pong.duration(), Some(3.0));
+            assert!(near(ping_pong.values_at(0.25), vec!(0.25)), "forwards");
+            assert!(near(ping_pong.values_at(1.25), vec!(0.75)), "backwards");
+            assert!(near(ping_pong.values_at(2.25), vec!(0.25)), "forwards again");
+            assert!(near(ping_pong.values_at(3.5), vec!(1.0)), "odd plays end at the end");
+            assert!(near(ping_pong.clone().repeat(1).values_at(3.5), vec!(0.0)),
+                "even ping-pong plays end at the start");
+            assert!(ping_pong.clone().repeat(::std::u32::MAX).duration().is_some());
+
+            let forever = EaseTimeline::<$type>::from_values(&[0.0])
+                .then(Linear::ease_in_out, 2.0, &[1.0])
+                .repeat_forever();
+            assert_eq!(forever.duration(), None);
+            assert!(near(forever.values_at(2001.0), vec!(0.5)));
+            assert!(!forever.has_finished(1_000_000.0));
+        }
+    }
+
+    #[test]
+    fn timeline_32() {
+        check_timeline!(f32);
+    }
+
+    #[test]
+    fn timeline_64() {
+        check_timeline!(f64);
+    }
+
+    #[test]
+    fn timeline_without_segments() {
+        let timeline = EaseTimeline::<f64>::from_values(&[3.0]).delay(1.0).hold(0.0);
+        assert_eq!(timeline.values_at(0.5), vec!(3.0));
+        assert_eq!(timeline.values_at(5.0), vec!(3.0));
+        assert!(timeline.has_finished(1.5));
+    }
 }
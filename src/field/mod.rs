@@ -0,0 +1,155 @@
+//! Gravitational field overlay, shading potential or acceleration magnitude of all bodies
+//!
+//! The overlay is worked out per pixel in a fragment shader, the functions here are the CPU
+//! reference the shader follows
+pub mod render;
+
+use cgmath::*;
+use compute::GRAVITY;
+use orbitbody::OrbitBody;
+use camera::Camera;
+use state::State;
+use std::f64;
+
+/// Samples per axis across the visible area to find the shading range
+const RANGE_SAMPLES: usize = 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldMode {
+    Off,
+    /// shade by potential magnitude
+    Potential,
+    /// shade by acceleration magnitude
+    Acceleration,
+    /// acceleration arrows on a screen grid
+    Vectors,
+}
+
+impl FieldMode {
+    /// The mode after this one, cycling back to `Off`
+    pub fn next(self) -> FieldMode {
+        match self {
+            FieldMode::Off => FieldMode::Potential,
+            FieldMode::Potential => FieldMode::Acceleration,
+            FieldMode::Acceleration => FieldMode::Vectors,
+            FieldMode::Vectors => FieldMode::Off,
+        }
+    }
+}
+
+/// Gravitational potential at a point. Inside a body it's that of a uniform sphere,
+/// so it stays finite & matches `acceleration_at`
+pub fn potential_at(bodies: &[OrbitBody], point: Vector2<f64>) -> f64 {
+    bodies.iter().map(|body| {
+        let dist = body.center.distance(point);
+        if dist >= body.radius {
+            -GRAVITY * body.mass / dist
+        }
+        else {
+            let r3 = body.radius.powi(3);
+            -GRAVITY * body.mass * (3.0 * body.radius * body.radius - dist * dist) / (2.0 * r3)
+        }
+    }).sum()
+}
+
+/// Gravitational acceleration at a point, falling linearly to zero at a body's center when inside
+pub fn acceleration_at(bodies: &[OrbitBody], point: Vector2<f64>) -> Vector2<f64> {
+    bodies.iter().fold(Vector2::zero(), |sum, body| {
+        let offset = body.center - point;
+        let dist = offset.magnitude().max(body.radius);
+        sum + offset * (GRAVITY * body.mass / (dist * dist * dist))
+    })
+}
+
+/// Magnitude the overlay shades by, `None` if the mode doesn't shade
+pub fn field_value(mode: FieldMode, bodies: &[OrbitBody], point: Vector2<f64>) -> Option<f64> {
+    match mode {
+        FieldMode::Potential => Some(potential_at(bodies, point).abs()),
+        FieldMode::Acceleration | FieldMode::Vectors => Some(acceleration_at(bodies, point).magnitude()),
+        FieldMode::Off => None,
+    }
+}
+
+/// (min, max) log10 field value across a grid of samples of the visible area, shading is
+/// scaled within this range
+pub fn visible_log_range(state: &State, camera: &Camera, mode: FieldMode) -> Option<(f32, f32)> {
+    let bodies = &state.drawables.orbit_bodies;
+    let (w, h) = (camera.viewport.width as usize, camera.viewport.height as usize);
+    let mut range: Option<(f64, f64)> = None;
+    for sx in 0..RANGE_SAMPLES + 1 {
+        for sy in 0..RANGE_SAMPLES + 1 {
+            let pixel = ((sx * w / RANGE_SAMPLES) as i32, (sy * h / RANGE_SAMPLES) as i32);
+            let point = camera.screen_to_world(pixel).cast();
+            let value = match field_value(mode, bodies, point) {
+                Some(value) if value > 0.0 => value.log10(),
+                _ => continue,
+            };
+            range = Some(match range {
+                Some((min, max)) => (min.min(value), max.max(value)),
+                None => (value, value),
+            });
+        }
+    }
+    range.map(|(min, max)| (min as f32, max as f32))
+}
+
+#[cfg(test)]
+mod field_test {
+    use super::*;
+    use orbitbody::palette_color;
+
+    fn body(x: f64, mass: f64) -> OrbitBody {
+        OrbitBody {
+            color: palette_color(0),
+            ..OrbitBody::new((x, 0.0).into(), (0.0, 0.0).into(), mass, 1.0)
+        }
+    }
+
+    #[test]
+    fn potential() {
+        let bodies = vec!(body(0.0, 100.0));
+        assert!((potential_at(&bodies, (10.0, 0.0).into()) - -GRAVITY * 10.0).abs() < 1e-12);
+        // continuous at the surface & deepest at the center
+        let inside = potential_at(&bodies, (0.999_999, 0.0).into());
+        let outside = potential_at(&bodies, (1.000_001, 0.0).into());
+        assert!((inside - outside).abs() < 1e-5);
+        assert!(potential_at(&bodies, (0.0, 0.0).into()) < inside);
+    }
+
+    #[test]
+    fn acceleration() {
+        let bodies = vec!(body(0.0, 100.0));
+        let accel = acceleration_at(&bodies, (0.0, 10.0).into());
+        assert!((accel - Vector2::new(0.0, -GRAVITY)).magnitude() < 1e-12);
+        assert_eq!(acceleration_at(&bodies, (0.0, 0.0).into()), Vector2::zero());
+
+        // cancels between equal masses
+        let pair = vec!(body(-5.0, 100.0), body(5.0, 100.0));
+        assert!(acceleration_at(&pair, (0.0, 0.0).into()).magnitude() < 1e-12);
+        assert!(acceleration_at(&pair, (0.0, 3.0).into()).y < 0.0);
+    }
+
+    #[test]
+    fn acceleration_is_potential_gradient() {
+        let bodies = vec!(body(0.0, 100.0), body(4.0, 30.0));
+        let point = Vector2::new(1.5, 2.5);
+        let h = 1e-5;
+        let gradient = Vector2::new(
+            potential_at(&bodies, point + Vector2::new(h, 0.0)) - potential_at(&bodies, point - Vector2::new(h, 0.0)),
+            potential_at(&bodies, point + Vector2::new(0.0, h)) - potential_at(&bodies, point - Vector2::new(0.0, h)),
+        ) / (2.0 * h);
+        assert!((acceleration_at(&bodies, point) + gradient).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn log_range() {
+        let mut state = State::new(100, 100);
+        state.camera_mut().zoom = 10.0;
+        state.drawables.orbit_bodies = vec!(body(0.0, 100.0));
+        let (min, max) = visible_log_range(&state, state.camera(), FieldMode::Potential).unwrap();
+        // furthest corner ~14.1 away, nearest sample is the center
+        assert!((min as f64 - (GRAVITY * 100.0 / 200f64.sqrt()).log10()).abs() < 1e-3);
+        assert!((max as f64 - (GRAVITY * 100.0 * 1.5).log10()).abs() < 1e-3);
+        assert_eq!(visible_log_range(&state, state.camera(), FieldMode::Off), None);
+    }
+}
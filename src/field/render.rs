@@ -0,0 +1,148 @@
+use super::*;
+use super::super::{ColorFormat, DepthFormat};
+use gfx::*;
+use gfx::traits::FactoryExt;
+use gfx;
+use gfx_shader_watch::*;
+use std::cmp::Ordering;
+
+/// Bodies the shader considers, the heaviest if there are more
+pub const MAX_FIELD_BODIES: usize = 256;
+
+#[derive(VertexData, Debug, Clone, Copy)]
+pub struct FieldVertex {
+    position: [f32; 2],
+}
+
+#[derive(ConstantBuffer, Debug, Clone, Copy)]
+pub struct FieldParams {
+    screen_size: [f32; 2],
+    zoom: f32,
+    aspect_ratio: f32,
+    rotation: f32,
+    /// `FieldMode` index
+    mode: u32,
+    /// 1 to draw contour lines, otherwise 0
+    contours: f32,
+    body_count: u32,
+    /// log10 field values shaded from dark to bright
+    log_range: [f32; 2],
+    std140_offset: [u32; 2],
+}
+
+#[derive(ConstantBuffer, Debug, Clone, Copy)]
+pub struct FieldBody {
+    /// relative to the camera origin, keeping f32 precision far from the world origin
+    center: [f32; 2],
+    /// gravitational constant × mass
+    gravity_mass: f32,
+    radius: f32,
+}
+
+gfx_defines! {
+    pipeline fieldpipe {
+        vbuf: VertexBuffer<FieldVertex> = (),
+        out: BlendTarget<ColorFormat> = ("out_color", state::ColorMask::all(), preset::blend::ALPHA),
+        out_depth: gfx::DepthTarget<DepthFormat> = preset::depth::LESS_EQUAL_TEST,
+        params: ConstantBuffer<FieldParams> = "field_params",
+        bodies: ConstantBuffer<FieldBody> = "field_bodies",
+    }
+}
+
+const FIELD_QUAD: [FieldVertex; 4] = [
+    FieldVertex{ position: [-1.0, 1.0] },
+    FieldVertex{ position: [1.0, 1.0] },
+    FieldVertex{ position: [1.0, -1.0] },
+    FieldVertex{ position: [-1.0, -1.0] }];
+
+/// Bodies for the shader, heaviest first if limited
+fn field_bodies(state: &State, camera: &Camera) -> Vec<FieldBody> {
+    let mut bodies: Vec<&OrbitBody> = state.drawables.orbit_bodies.iter().collect();
+    if bodies.len() > MAX_FIELD_BODIES {
+        bodies.sort_by(|a, b| b.mass.partial_cmp(&a.mass).unwrap_or(Ordering::Equal));
+        bodies.truncate(MAX_FIELD_BODIES);
+    }
+    let origin: Vector2<f64> = camera.origin.cast();
+    bodies.iter().map(|body| FieldBody {
+        center: (body.center - origin).cast::<f32>().into(),
+        gravity_mass: (GRAVITY * body.mass) as f32,
+        radius: body.radius as f32,
+    }).collect()
+}
+
+pub struct FieldBrush<R: Resources, F: Factory<R>> {
+    pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = fieldpipe),
+    slice: Slice<R>,
+    data: fieldpipe::Data<R>,
+}
+
+impl<R: Resources, F: Factory<R>> FieldBrush<R, F> {
+    pub fn new(mut factory: F,
+               target: &handle::RenderTargetView<R, ColorFormat>,
+               depth_target: &handle::DepthStencilView<R, DepthFormat>)
+               -> FieldBrush<R, F>
+    {
+        let (vertex_buffer, slice) = factory
+            .create_vertex_buffer_with_slice(&FIELD_QUAD, &[0u16, 1, 2, 0, 2, 3] as &[u16]);
+        let data = fieldpipe::Data {
+            vbuf: vertex_buffer,
+            out: target.clone(),
+            out_depth: depth_target.clone(),
+            params: factory.create_constant_buffer(1),
+            bodies: factory.create_constant_buffer(MAX_FIELD_BODIES),
+        };
+
+        let pso_cell = debug_watcher_pso_cell!(
+            pipe = fieldpipe,
+            vertex_shader = "shader/vert.glsl",
+            fragment_shader = "shader/frag.glsl",
+            factory = factory).expect("Field pso");
+
+        FieldBrush { pso_cell, slice, data }
+    }
+
+    /// Draws the overlay of the state's field mode, call after the background
+    pub fn draw<C: CommandBuffer<R>>(&mut self, encoder: &mut Encoder<R, C>, state: &State, camera: &Camera) {
+        let log_range = match visible_log_range(state, camera, state.field_mode) {
+            Some((min, max)) => [min, max],
+            None => return,
+        };
+        let bodies = field_bodies(state, camera);
+        let params = FieldParams {
+            screen_size: [camera.viewport.width as f32, camera.viewport.height as f32],
+            zoom: camera.zoom,
+            aspect_ratio: camera.aspect_ratio(),
+            rotation: camera.rotation,
+            mode: state.field_mode as u32,
+            contours: if state.field_contours { 1.0 } else { 0.0 },
+            body_count: bodies.len() as u32,
+            log_range,
+            std140_offset: [0; 2],
+        };
+        encoder.update_constant_buffer(&self.data.params, &params);
+        encoder.update_buffer(&self.data.bodies, &bodies, 0).expect("Field bodies");
+        encoder.draw(&self.slice, self.pso_cell.pso(), &self.data);
+    }
+}
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+    use orbitbody::palette_color;
+
+    #[test]
+    fn heaviest_bodies_relative_to_origin() {
+        let mut state = State::new(100, 100);
+        state.camera_mut().origin = Vector2::new(1000.0, 0.0);
+        state.drawables.orbit_bodies = (0..MAX_FIELD_BODIES + 10).map(|idx| OrbitBody {
+            color: palette_color(idx),
+            ..OrbitBody::new((1000.0 + idx as f64, 0.0).into(), (0.0, 0.0).into(), idx as f64, 0.5)
+        }).collect();
+
+        let bodies = field_bodies(&state, state.camera());
+        assert_eq!(bodies.len(), MAX_FIELD_BODIES);
+        let heaviest = (MAX_FIELD_BODIES + 9) as f32;
+        assert_eq!(bodies[0].center, [heaviest, 0.0]);
+        assert_eq!(bodies[0].gravity_mass, (GRAVITY * heaviest as f64) as f32);
+    }
+}
@@ -0,0 +1,141 @@
+#version 330 core
+
+layout(std140) uniform;
+
+struct FieldBody {
+    vec2 center;
+    float gravity_mass;
+    float radius;
+};
+
+uniform field_params {
+    vec2 screen_size;
+    float zoom;
+    float aspect_ratio;
+    float rotation;
+    uint mode;
+    float contours;
+    uint body_count;
+    vec2 log_range;
+};
+
+uniform field_bodies {
+    FieldBody bodies[256];
+};
+
+in vec2 screen_pos;
+
+out vec4 out_color;
+
+// FieldMode indices
+const uint POTENTIAL = 1u;
+const uint VECTORS = 3u;
+// arrow grid cell size in pixels
+const float arrow_cell = 48.0;
+// contour lines per decade of field value
+const float contours_per_decade = 4.0;
+
+vec2 rotate(vec2 v, float angle) {
+    float s = sin(angle);
+    float c = cos(angle);
+    return vec2(c * v.x - s * v.y, s * v.x + c * v.y);
+}
+
+/// World position relative to the origin of a clip space position
+vec2 world_at(vec2 clip) {
+    return rotate(clip * vec2(aspect_ratio, 1.0) * zoom, rotation);
+}
+
+/// see field::potential_at
+float potential_at(vec2 point) {
+    float potential = 0.0;
+    for (uint i = 0u; i < body_count; i++) {
+        FieldBody body = bodies[i];
+        float dist = distance(body.center, point);
+        if (dist >= body.radius) {
+            potential -= body.gravity_mass / dist;
+        }
+        else {
+            float r = body.radius;
+            potential -= body.gravity_mass * (3.0 * r * r - dist * dist) / (2.0 * r * r * r);
+        }
+    }
+    return potential;
+}
+
+/// see field::acceleration_at
+vec2 acceleration_at(vec2 point) {
+    vec2 acceleration = vec2(0.0);
+    for (uint i = 0u; i < body_count; i++) {
+        FieldBody body = bodies[i];
+        vec2 offset = body.center - point;
+        float dist = max(length(offset), body.radius);
+        acceleration += offset * (body.gravity_mass / (dist * dist * dist));
+    }
+    return acceleration;
+}
+
+/// Fraction of the way through the visible log10 range
+float shade_fraction(float value) {
+    float span = max(log_range.y - log_range.x, 0.0001);
+    return clamp((log(max(value, 1e-30)) / log(10.0) - log_range.x) / span, 0.0, 1.0);
+}
+
+/// Dark blue through purple to orange
+vec3 heat(float t) {
+    vec3 low = vec3(0.05, 0.1, 0.35);
+    vec3 mid = vec3(0.55, 0.15, 0.55);
+    vec3 high = vec3(1.0, 0.65, 0.2);
+    return t < 0.5 ? mix(low, mid, t * 2.0) : mix(mid, high, t * 2.0 - 1.0);
+}
+
+/// Distance from a point to the segment a->b
+float segment_distance(vec2 p, vec2 a, vec2 b) {
+    vec2 ab = b - a;
+    float t = clamp(dot(p - a, ab) / max(dot(ab, ab), 0.0001), 0.0, 1.0);
+    return distance(a + t * ab, p);
+}
+
+/// Arrow of the acceleration at the center of this pixel's grid cell
+vec4 arrows() {
+    vec2 pixel = gl_FragCoord.xy;
+    vec2 cell_center = (floor(pixel / arrow_cell) + 0.5) * arrow_cell;
+    vec2 accel = acceleration_at(world_at(cell_center / screen_size * 2.0 - 1.0));
+    float magnitude = length(accel);
+    if (magnitude <= 0.0) {
+        discard;
+    }
+    // screen direction, undoing the camera rotation
+    vec2 dir = rotate(accel / magnitude, -rotation);
+    float len = arrow_cell * mix(0.15, 0.45, shade_fraction(magnitude));
+    vec2 tail = cell_center - dir * len * 0.5;
+    vec2 tip = cell_center + dir * len * 0.5;
+    vec2 side = vec2(-dir.y, dir.x);
+    float dist = min(segment_distance(pixel, tail, tip),
+                     min(segment_distance(pixel, tip, tip - dir * 6.0 + side * 4.0),
+                         segment_distance(pixel, tip, tip - dir * 6.0 - side * 4.0)));
+    float alpha = 1.0 - smoothstep(0.5, 1.5, dist);
+    if (alpha <= 0.0) {
+        discard;
+    }
+    return vec4(heat(shade_fraction(magnitude)), 0.8 * alpha);
+}
+
+void main() {
+    if (mode == VECTORS) {
+        out_color = arrows();
+        return;
+    }
+    vec2 point = world_at(screen_pos);
+    float value = mode == POTENTIAL ? abs(potential_at(point)) : length(acceleration_at(point));
+    float t = shade_fraction(value);
+    out_color = vec4(heat(t), 0.35);
+
+    if (contours > 0.5) {
+        // anti-aliased lines where the log value crosses each contour step
+        float level = log(max(value, 1e-30)) / log(10.0) * contours_per_decade;
+        float width = fwidth(level);
+        float line = 1.0 - smoothstep(0.0, 1.5 * width, abs(fract(level + 0.5) - 0.5));
+        out_color = mix(out_color, vec4(0.9, 0.9, 0.9, 0.6), line);
+    }
+}
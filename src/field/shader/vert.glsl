@@ -0,0 +1,11 @@
+#version 330 core
+
+in vec2 position;
+
+out vec2 screen_pos;
+
+void main() {
+    screen_pos = position;
+    // just in front of the background
+    gl_Position = vec4(position, 0.95, 1.0);
+}
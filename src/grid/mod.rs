@@ -0,0 +1,124 @@
+//! Adaptive world grid, scale bar & cursor coordinate readout
+pub mod render;
+
+use cgmath::*;
+use camera::Camera;
+
+/// Grid lines are at least this many pixels apart
+pub const MIN_LINE_PIXELS: f32 = 40.0;
+/// Screen width of the scale bar
+pub const SCALE_BAR_PIXELS: i32 = 150;
+
+/// Smallest 1, 2 or 5 ×10^n length at least `min`
+pub fn nice_length(min: f32) -> f32 {
+    let exponent = min.log10().floor() as i32;
+    // divide for small lengths, as 10^-n isn't exact
+    let scaled = |step: f32| if exponent >= 0 { step * 10f32.powi(exponent) } else { step / 10f32.powi(-exponent) };
+    [1.0, 2.0, 5.0, 10.0].iter()
+        .map(|step| scaled(*step))
+        .find(|length| *length >= min * 0.9999)
+        .unwrap_or_else(|| scaled(10.0))
+}
+
+/// World distance between grid lines at the current zoom
+pub fn grid_spacing(camera: &Camera) -> f32 {
+    nice_length(MIN_LINE_PIXELS * camera.world_per_pixel())
+}
+
+/// Coordinates of the grid lines crossing the `min`..`max` range, ie x coords of vertical lines
+pub fn line_coords(spacing: f32, min: f32, max: f32) -> Vec<f32> {
+    let first = (min / spacing).ceil() as i64;
+    let last = (max / spacing).floor() as i64;
+    (first..last + 1).map(|n| n as f32 * spacing).collect()
+}
+
+/// Screen pixel (start, end) of the scale bar in the bottom-left corner
+pub fn scale_bar_pixels(camera: &Camera) -> ((i32, i32), (i32, i32)) {
+    let y = camera.viewport.height as i32 - 30;
+    ((20, y), (20 + SCALE_BAR_PIXELS, y))
+}
+
+/// World length spanned by the scale bar
+pub fn scale_bar_length(camera: &Camera) -> f32 {
+    SCALE_BAR_PIXELS as f32 * camera.world_per_pixel()
+}
+
+/// Formats a world length or coordinate to 3 significant figures, in exponent form if very
+/// large or small
+pub fn format_world(value: f32) -> String {
+    let magnitude = value.abs();
+    if magnitude != 0.0 && (magnitude < 0.001 || magnitude >= 100_000.0) {
+        return format!("{:.2e}", value);
+    }
+    let decimals = if magnitude == 0.0 { 0 } else { (2 - magnitude.log10().floor() as i32).max(0) };
+    format!("{:.*}", decimals as usize, value)
+}
+
+/// World coordinate readout of a window pixel, ie `(1.25, -30.0)`
+pub fn cursor_readout(camera: &Camera, cursor: (i32, i32)) -> String {
+    let world: Vector2<f32> = camera.window_to_world(cursor);
+    format!("({}, {})", format_world(world.x), format_world(world.y))
+}
+
+#[cfg(test)]
+mod grid_test {
+    use super::*;
+    use camera::Viewport;
+
+    #[test]
+    fn nice_lengths() {
+        assert_eq!(nice_length(1.0), 1.0);
+        assert_eq!(nice_length(1.1), 2.0);
+        assert_eq!(nice_length(3.0), 5.0);
+        assert_eq!(nice_length(7.0), 10.0);
+        assert_eq!(nice_length(0.013), 0.02);
+        assert_eq!(nice_length(420.0), 500.0);
+    }
+
+    #[test]
+    fn spacing_adapts_to_zoom() {
+        let mut camera = Camera::new(Viewport::fullscreen(800, 800));
+        camera.zoom = 10.0; // 0.025 world per pixel, so 40px is 1.0
+        assert_eq!(grid_spacing(&camera), 1.0);
+        camera.zoom = 20.0;
+        assert_eq!(grid_spacing(&camera), 2.0);
+        camera.zoom = 0.5;
+        assert_eq!(grid_spacing(&camera), 0.05);
+    }
+
+    #[test]
+    fn visible_line_coords() {
+        assert_eq!(line_coords(2.0, -3.0, 4.5), vec!(-2.0, 0.0, 2.0, 4.0));
+        assert_eq!(line_coords(2.0, 2.0, 4.0), vec!(2.0, 4.0));
+        assert!(line_coords(5.0, 1.0, 4.0).is_empty());
+    }
+
+    #[test]
+    fn formatting() {
+        assert_eq!(format_world(0.0), "0");
+        assert_eq!(format_world(1.0), "1.00");
+        assert_eq!(format_world(-12.34), "-12.3");
+        assert_eq!(format_world(150.0), "150");
+        assert_eq!(format_world(0.0125), "0.0125");
+        assert_eq!(format_world(2_500_000.0), "2.50e6");
+    }
+
+    #[test]
+    fn scale_bar() {
+        let mut camera = Camera::new(Viewport::fullscreen(800, 400));
+        camera.zoom = 2.0;
+        assert_eq!(scale_bar_length(&camera), 1.5);
+        assert_eq!(scale_bar_pixels(&camera), ((20, 370), (170, 370)));
+    }
+
+    #[test]
+    fn cursor_world_coordinate() {
+        let mut camera = Camera::new(Viewport::fullscreen(100, 100));
+        camera.zoom = 1.0;
+        assert_eq!(cursor_readout(&camera, (75, 50)), "(0.500, 0)");
+
+        // window pixels are offset by the viewport
+        camera.viewport = Viewport { left: 100, top: 0, width: 100, height: 100 };
+        assert_eq!(cursor_readout(&camera, (175, 50)), "(0.500, 0)");
+    }
+}
@@ -0,0 +1,86 @@
+use super::*;
+use super::super::{UserViewTransform, ColorFormat, DepthFormat};
+use lines::render::{LineBrush, LineVertex};
+use gfx::*;
+use gfx_text::{HorizontalAnchor, VerticalAnchor};
+use gfx_text;
+
+const LINE_COLOR: [f32; 4] = [0.4, 0.6, 0.7, 0.12];
+const AXIS_COLOR: [f32; 4] = [0.4, 0.6, 0.7, 0.35];
+const SCALE_BAR_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 0.9];
+const TEXT_COLOR: [f32; 4] = [0.8, 0.8, 0.8, 1.0];
+/// scale bar end tick height in pixels
+const TICK_PIXELS: i32 = 6;
+/// behind curves & bodies
+const GRID_DEPTH: f32 = 0.9;
+
+/// Line list vertices of the visible grid & the scale bar
+fn grid_vertices(camera: &Camera) -> Vec<LineVertex> {
+    let (min, max) = camera.visible_world_range();
+    let spacing = grid_spacing(camera);
+    let color = |coord: f32| if coord == 0.0 { AXIS_COLOR } else { LINE_COLOR };
+    let mut verts = Vec::new();
+    for x in line_coords(spacing, min.x, max.x) {
+        verts.push(LineVertex { position: [x, min.y], color: color(x) });
+        verts.push(LineVertex { position: [x, max.y], color: color(x) });
+    }
+    for y in line_coords(spacing, min.y, max.y) {
+        verts.push(LineVertex { position: [min.x, y], color: color(y) });
+        verts.push(LineVertex { position: [max.x, y], color: color(y) });
+    }
+
+    // scale bar with end ticks, in pixels mapped to the world so it stays level on screen
+    let ((x1, y), (x2, _)) = scale_bar_pixels(camera);
+    let pixel_lines = [((x1, y), (x2, y)), ((x1, y - TICK_PIXELS), (x1, y)), ((x2, y - TICK_PIXELS), (x2, y))];
+    for &(start, end) in &pixel_lines {
+        verts.push(LineVertex { position: camera.screen_to_world(start).into(), color: SCALE_BAR_COLOR });
+        verts.push(LineVertex { position: camera.screen_to_world(end).into(), color: SCALE_BAR_COLOR });
+    }
+    verts
+}
+
+pub struct GridBrush<R: Resources, F: Factory<R>> {
+    lines: LineBrush<R, F>,
+    text: gfx_text::Renderer<R, F>,
+}
+
+impl<R: Resources, F: Factory<R> + Clone> GridBrush<R, F> {
+    pub fn new(factory: F,
+               target: &handle::RenderTargetView<R, ColorFormat>,
+               depth_target: &handle::DepthStencilView<R, DepthFormat>)
+               -> GridBrush<R, F>
+    {
+        let text = gfx_text::new(factory.clone()).with_size(13).unwrap();
+        let lines = LineBrush::new(factory, target, depth_target, GRID_DEPTH);
+        GridBrush { lines, text }
+    }
+
+    /// Draws grid lines & the scale bar, call before drawing bodies & curves
+    pub fn draw<C>(&mut self,
+                   encoder: &mut Encoder<R, C>,
+                   transform: &UserViewTransform,
+                   camera: &Camera) where C: CommandBuffer<R> {
+        self.lines.draw_lines(encoder, transform, &grid_vertices(camera));
+    }
+
+    /// Draws the scale bar length, & the coordinate under the `cursor` window pixel if the
+    /// cursor is over this camera, on top of everything else
+    pub fn draw_text<C: CommandBuffer<R>, T: format::RenderFormat>(&mut self,
+                                                                  encoder: &mut Encoder<R, C>,
+                                                                  target: &handle::RenderTargetView<R, T>,
+                                                                  camera: &Camera,
+                                                                  cursor: Option<(i32, i32)>)
+                                                                  -> Result<(), gfx_text::Error>
+    {
+        let ((x1, y), (x2, _)) = scale_bar_pixels(camera);
+        self.text.add_anchored(&format_world(scale_bar_length(camera)), [(x1 + x2) / 2, y - TICK_PIXELS - 2],
+                               HorizontalAnchor::Center, VerticalAnchor::Bottom,
+                               TEXT_COLOR);
+        if let Some(cursor) = cursor {
+            self.text.add_anchored(&cursor_readout(camera, cursor), [camera.viewport.width as i32 - 5, y],
+                                   HorizontalAnchor::Right, VerticalAnchor::Center,
+                                   TEXT_COLOR);
+        }
+        self.text.draw(encoder, target)
+    }
+}
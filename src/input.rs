@@ -2,43 +2,66 @@ use glutin::*;
 use state::*;
 use ease::*;
 use cgmath::*;
-use time;
+use clock::Clock;
 use easer::functions::*;
 use uuid::Uuid;
+use orbitbody::OrbitBody;
+use bindings::*;
+use minimap::{self, Overview};
+use camera::{Camera, RotatingFrame};
+use camerapath::{CameraPath, PathPlayback};
+use std::cmp::Ordering;
+use std::f32;
 use std::time::{Instant, Duration};
 
 const MIN_ZOOM: f32 = 0.5;
 const MAX_ZOOM: f32 = 70.0;
 const ZOOM_DURATION_SECONDS: f32 = 1.0;
-const DBL_CLICK_MS: u64 = 500;
+/// trackpad scroll pixels equivalent to a wheel notch
+const PIXELS_PER_ZOOM_STEP: f32 = 120.0;
+/// recent drag movement considered for the velocity of a flick
+const FLICK_SAMPLE_MS: u64 = 80;
+/// exponential decay rate of flick velocity per second
+const FLICK_FRICTION: f32 = 4.0;
+/// keyboard pan speed in half screen heights per second
+const KEY_PAN_SPEED: f32 = 1.5;
+/// time to ease between keyboard pan velocities
+const KEY_PAN_EASE_SECONDS: f32 = 0.2;
+/// fraction of the framed area added as padding on each side
+const AUTO_FRAME_PADDING: f32 = 0.15;
+/// world distance from the framed bodies' center of mass beyond which bodies are ignored
+const AUTO_FRAME_OUTLIER_DISTANCE: f64 = 80.0;
+/// auto-frame target changes, as a fraction of the zoom, beyond which the camera eases over rather
+/// than tracking the target directly
+const AUTO_FRAME_EASE_THRESHOLD: f32 = 0.05;
 
+/// Eases the camera zoom & origin, times are `Clock::wall` seconds
 #[derive(Clone, Debug)]
 pub struct Zoomer {
+    /// clock time the easer started, the easer itself runs from 0 to keep f32 precision
+    started: f64,
     easer: Easer<f32>,
 }
 
 impl Zoomer {
-    pub fn zoom_to_screen(zoom: f32, sceen_location: (i32, i32), current: &State) -> Zoomer {
-        let mut after_state = current.clone();
-        after_state.zoom = zoom;
+    /// Returns the origin keeping the world location at a screen location fixed after zooming
+    pub fn origin_zooming_to_screen(zoom: f32, sceen_location: (i32, i32), current: &Camera) -> Vector2<f32> {
+        let mut after = current.clone();
+        after.zoom = zoom;
         let zoom_to = current.screen_to_world(sceen_location);
-        let zoomed_to = after_state.screen_to_world(sceen_location);
-        let new_origin = current.origin + zoom_to - zoomed_to;
+        let zoomed_to = after.screen_to_world(sceen_location);
+        current.origin + zoom_to - zoomed_to
+    }
 
-        Zoomer {
-            easer: Easer::using(Expo::ease_out)
-                    .start(time::precise_time_s() as f32)
-                    .duration(ZOOM_DURATION_SECONDS)
-                    .add_transition(current.zoom, zoom)
-                    .add_transition(current.origin.x, new_origin.x)
-                    .add_transition(current.origin.y, new_origin.y)
-        }
+    pub fn zoom_to_screen(zoom: f32, sceen_location: (i32, i32), current: &Camera, now: f64) -> Zoomer {
+        let new_origin = Zoomer::origin_zooming_to_screen(zoom, sceen_location, current);
+        Zoomer::zoom_to_world(zoom, new_origin.into(), current, now)
     }
 
-    pub fn zoom_to_world(zoom: f32, world_location: (f32, f32), current: &State) -> Zoomer {
+    pub fn zoom_to_world(zoom: f32, world_location: (f32, f32), current: &Camera, now: f64) -> Zoomer {
         Zoomer {
+            started: now,
             easer: Easer::using(Expo::ease_out)
-                    .start(time::precise_time_s() as f32)
                     .duration(ZOOM_DURATION_SECONDS)
                     .add_transition(current.zoom, zoom)
                     .add_transition(current.origin.x, world_location.0)
@@ -46,75 +69,346 @@ impl Zoomer {
         }
     }
 
-    pub fn just_zoom(zoom: f32, current: &State) -> Zoomer {
-        Zoomer {
-            easer: Easer::using(Expo::ease_out)
-                    .start(time::precise_time_s() as f32)
-                    .duration(ZOOM_DURATION_SECONDS)
-                    .add_transition(current.zoom, zoom)
-                    .add_transition(current.origin.x, current.origin.x)
-                    .add_transition(current.origin.y, current.origin.y)
-        }
+    pub fn just_zoom(zoom: f32, current: &Camera, now: f64) -> Zoomer {
+        Zoomer::zoom_to_world(zoom, current.origin.into(), current, now)
+    }
+
+    /// Easer time at a clock time
+    fn easer_time(&self, now: f64) -> f32 {
+        (now - self.started) as f32
     }
 
     pub fn zoom_destination(&self) -> f32 {
         self.easer.transitions[0].1
     }
 
-    pub fn zoom_at(&self, time: f32) -> f32 {
-        let vals = self.easer.values_at(time);
+    pub fn zoom_at(&self, time: f64) -> f32 {
+        let vals = self.easer.values_at(self.easer_time(time));
         vals[0]
     }
 
-    pub fn origin_at(&self, time: f32) -> Vector2<f32> {
-        let vals = self.easer.values_at(time);
+    pub fn origin_at(&self, time: f64) -> Vector2<f32> {
+        let vals = self.easer.values_at(self.easer_time(time));
         Vector2::new(vals[1], vals[2])
     }
 
-    pub fn finished_at(&self, time: f32) -> bool {
-        self.easer.has_finished(time)
+    pub fn finished_at(&self, time: f64) -> bool {
+        self.easer.has_finished(self.easer_time(time))
     }
 
-    pub fn update_origin_destination<V: Into<(f32, f32)>>(&mut self, new: V) {
+    /// Changes the origin destination mid-zoom, keeping the current origin & its velocity
+    pub fn update_origin_destination<V: Into<(f32, f32)>>(&mut self, new: V, now: f64) {
         let (newx, newy) = new.into();
-        self.easer.transitions[1].1 = newx;
-        self.easer.transitions[2].1 = newy;
+        let t = self.easer_time(now);
+        self.easer.retarget(1, t, newx);
+        self.easer.retarget(2, t, newy);
+    }
+
+    /// Changes the zoom destination mid-zoom, keeping the current zoom & its velocity
+    pub fn update_zoom_destination(&mut self, zoom: f32, now: f64) {
+        let t = self.easer_time(now);
+        self.easer.retarget(0, t, zoom);
+    }
+
+    /// Restarts a full length zoom toward a new destination from the current values,
+    /// continuing at the current velocities
+    pub fn retarget(&mut self, zoom: f32, origin: Vector2<f32>, now: f64) {
+        let t = self.easer_time(now);
+        let current = self.easer.values_at(t);
+        let velocities = self.easer.velocities_at(t);
+
+        self.started = now;
+        self.easer = Easer::using(Expo::ease_out)
+            .duration(ZOOM_DURATION_SECONDS)
+            .add_transition(current[0], zoom)
+            .add_transition(current[1], origin.x)
+            .add_transition(current[2], origin.y)
+            .initial_velocities(&velocities);
     }
 }
 
+/// Camera task continuously fitting a set of bodies into view
 #[derive(Clone, Debug)]
-pub struct Tasks {
+pub struct AutoFrame {
+    /// bodies to keep in view, all bodies if `None`
+    pub bodies: Option<Vec<Uuid>>,
+    /// fraction of the framed area added as padding on each side
+    pub padding: f32,
+    /// bodies further than this from the framed bodies' center of mass are ignored
+    pub outlier_distance: f64,
+}
+
+impl AutoFrame {
+    pub fn all() -> AutoFrame {
+        AutoFrame {
+            bodies: None,
+            padding: AUTO_FRAME_PADDING,
+            outlier_distance: AUTO_FRAME_OUTLIER_DISTANCE,
+        }
+    }
+
+    pub fn bodies(ids: Vec<Uuid>) -> AutoFrame {
+        AutoFrame { bodies: Some(ids), ..AutoFrame::all() }
+    }
+
+    /// Returns (zoom, origin) fitting the framed bodies in view, `None` if there is nothing to frame
+    pub fn target(&self, bodies: &[OrbitBody], camera: &Camera) -> Option<(f32, Vector2<f32>)> {
+        let mut framed: Vec<&OrbitBody> = bodies.iter()
+            .filter(|b| self.bodies.as_ref().map(|ids| ids.contains(&b.id)).unwrap_or(true))
+            .collect();
+
+        // drop the furthest body from the center of mass while it's an outlier
+        let outlier_distance2 = self.outlier_distance * self.outlier_distance;
+        while framed.len() > 1 {
+            let total_mass: f64 = framed.iter().map(|b| b.mass).sum();
+            let center_of_mass = framed.iter()
+                .fold(Vector2::zero(), |sum: Vector2<f64>, b| sum + b.center * b.mass) / total_mass;
+            let (furthest_idx, furthest_distance2) = framed.iter()
+                .map(|b| b.center.distance2(center_of_mass))
+                .enumerate()
+                .fold((0, 0.0), |max, (idx, d2)| if d2 > max.1 { (idx, d2) } else { max });
+            if furthest_distance2 <= outlier_distance2 {
+                break;
+            }
+            framed.remove(furthest_idx);
+        }
+        if framed.is_empty() {
+            return None;
+        }
+
+        let mut min: Vector2<f64> = Vector2::new(1./0., 1./0.);
+        let mut max: Vector2<f64> = Vector2::new(-1./0., -1./0.);
+        for body in framed {
+            min.x = min.x.min(body.center.x - body.radius);
+            min.y = min.y.min(body.center.y - body.radius);
+            max.x = max.x.max(body.center.x + body.radius);
+            max.y = max.y.max(body.center.y + body.radius);
+        }
+        let (min, max): (Vector2<f32>, Vector2<f32>) = (min.cast(), max.cast());
+
+        let half_size = (max - min) * (0.5 + self.padding);
+        let zoom = half_size.y.max(half_size.x / camera.aspect_ratio()).max(MIN_ZOOM).min(MAX_ZOOM);
+        Some((zoom, (min + max) / 2.0))
+    }
+}
+
+/// Camera task rotating with the line joining two bodies, so both appear stationary
+#[derive(Clone, Debug)]
+pub struct CoRotation {
+    /// (a, b) bodies, the view is rotated so the line a -> b points right
+    pub bodies: (Uuid, Uuid),
+    /// last (pivot, angle) frame used to keep the camera fixed relative to the rotating frame
+    last_frame: Option<(Vector2<f64>, f64)>,
+}
+
+impl CoRotation {
+    pub fn new(a: Uuid, b: Uuid) -> CoRotation {
+        CoRotation { bodies: (a, b), last_frame: None }
+    }
+
+    /// Returns the (pivot, angle) of the rotating frame for a body list,
+    /// where the pivot is the pair's center of mass
+    pub fn frame(&self, bodies: &[OrbitBody]) -> Option<(Vector2<f64>, f64)> {
+        let a = bodies.iter().find(|b| b.id == self.bodies.0)?;
+        let b = bodies.iter().find(|b| b.id == self.bodies.1)?;
+        let pivot = (a.center * a.mass + b.center * b.mass) / (a.mass + b.mass);
+        let line = b.center - a.center;
+        Some((pivot, line.y.atan2(line.x)))
+    }
+
+    /// Moves & rotates the camera with the frame, returns false if the bodies no longer exist
+    fn update(&mut self, camera: &mut Camera, bodies: &[OrbitBody]) -> bool {
+        match self.frame(bodies) {
+            Some((pivot, angle)) => {
+                let origin: Vector2<f64> = camera.origin.cast();
+                let offset = match self.last_frame {
+                    Some((last_pivot, last_angle)) => rotate(origin - last_pivot, -last_angle),
+                    None => rotate(origin - pivot, -angle),
+                };
+                camera.origin = (pivot + rotate(offset, angle)).cast();
+                camera.rotation = angle as f32;
+                camera.frame = Some(RotatingFrame { bodies: self.bodies, pivot, angle });
+                self.last_frame = Some((pivot, angle));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Tasks moving a single camera
+#[derive(Clone, Debug, Default)]
+pub struct CameraTasks {
     pub zoom: Option<Zoomer>,
     pub follow: Option<Uuid>,
+    pub frame: Option<AutoFrame>,
+    pub path: Option<PathPlayback>,
+    pub corotate: Option<CoRotation>,
 }
 
-impl Tasks {
-    pub fn new() -> Tasks {
-        Tasks { zoom: None, follow: None }
+impl CameraTasks {
+    /// Stops any tasks moving the camera
+    pub fn cancel(&mut self) {
+        self.zoom = None;
+        self.follow = None;
+        self.frame = None;
+        if self.path.take().is_some() {
+            info!("Camera path stopped");
+        }
     }
 
-    pub fn update(&mut self, mut state: &mut State) {
+    fn update(&mut self, camera: &mut Camera, bodies: &[OrbitBody], now: f64) {
+        if let Some(mut corotation) = self.corotate.take() {
+            if corotation.update(camera, bodies) {
+                self.corotate = Some(corotation);
+            }
+            else {
+                camera.rotation = 0.0;
+                camera.frame = None;
+            }
+        }
+
+        if let Some(playback) = self.path.take() {
+            let (zoom, origin) = playback.camera(bodies);
+            camera.zoom = zoom;
+            camera.origin = origin;
+            if playback.finished() {
+                info!("Camera path finished");
+            }
+            else {
+                self.path = Some(playback);
+            }
+        }
+
+        if let Some(frame) = self.frame.take() {
+            if let Some((zoom, origin)) = frame.target(bodies, camera) {
+                match self.zoom {
+                    Some(ref mut zoomer) => {
+                        zoomer.update_zoom_destination(zoom, now);
+                        zoomer.update_origin_destination(origin, now);
+                    }
+                    None => {
+                        let threshold = AUTO_FRAME_EASE_THRESHOLD * camera.zoom;
+                        if (zoom - camera.zoom).abs() > threshold || origin.distance(camera.origin) > threshold {
+                            self.zoom = Some(Zoomer::zoom_to_world(zoom, origin.into(), camera, now));
+                        }
+                        else {
+                            // bodies drift a little each step, track them rather than restart an ease
+                            camera.zoom = zoom;
+                            camera.origin = origin;
+                        }
+                    }
+                }
+            }
+            self.frame = Some(frame);
+        }
+
         let mut following = None;
         if let Some(id) = self.follow.take() {
-            following = state.drawables.orbit_bodies.iter().find(|b| b.id == id);
+            following = bodies.iter().find(|b| b.id == id);
         }
 
         if let Some(mut zoomer) = self.zoom.take() {
             if let Some(body) = following {
-                zoomer.update_origin_destination(body.center.cast());
+                zoomer.update_origin_destination(body.center.cast(), now);
                 self.follow = Some(body.id);
             }
-            let now = time::precise_time_s() as f32;
-            state.zoom = zoomer.zoom_at(now);
-            state.origin = zoomer.origin_at(now);
+            camera.zoom = zoomer.zoom_at(now);
+            camera.origin = zoomer.origin_at(now);
             if !zoomer.finished_at(now) {
                 self.zoom = Some(zoomer);
             }
         }
         else if let Some(body) = following {
-            state.origin = (body.center.x as f32, body.center.y as f32).into();
+            camera.origin = (body.center.x as f32, body.center.y as f32).into();
             self.follow = Some(body.id);
         }
+        camera.following = self.follow;
+    }
+
+    /// Starts following a body, easing the camera toward it
+    pub fn follow_body(&mut self, body: &OrbitBody, camera: &Camera, now: f64) {
+        info!("Following {}", body.display_name());
+        self.zoom = Some(Zoomer::zoom_to_world(camera.zoom, body.center.cast().into(), camera, now));
+        self.follow = Some(body.id);
+        self.frame = None;
+    }
+
+    /// Starts keeping the framed bodies in view, replacing any follow
+    pub fn auto_frame(&mut self, frame: AutoFrame) {
+        self.follow = None;
+        self.frame = Some(frame);
+    }
+
+    /// Plays a camera path from the current camera, replacing other camera tasks
+    pub fn play_path(&mut self, path: CameraPath, camera: &Camera) {
+        info!("Playing camera path, {:.1}s{}", path.duration(), if path.looping { " looping" } else { "" });
+        self.cancel();
+        self.path = Some(PathPlayback::new(path, camera));
+    }
+
+    /// Smallest zoom the camera is at or easing to
+    fn nearest_zoom(&self, camera: &Camera) -> f32 {
+        self.zoom.as_ref().map(|z| z.zoom_destination()).unwrap_or(camera.zoom).min(camera.zoom)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tasks {
+    /// tasks of each camera, by camera index
+    pub cameras: Vec<CameraTasks>,
+    /// time source for camera animations
+    pub clock: Clock,
+}
+
+impl Tasks {
+    pub fn new() -> Tasks {
+        Tasks::with_clock(Clock::system())
+    }
+
+    pub fn with_clock(clock: Clock) -> Tasks {
+        Tasks { cameras: vec!(CameraTasks::default()), clock }
+    }
+
+    /// Tasks of the camera input controls
+    pub fn active(&self, state: &State) -> &CameraTasks {
+        &self.cameras[state.active_camera]
+    }
+
+    pub fn active_mut(&mut self, state: &State) -> &mut CameraTasks {
+        &mut self.cameras[state.active_camera]
+    }
+
+    pub fn update(&mut self, state: &mut State) {
+        let now = self.clock.wall();
+        self.cameras.resize(state.cameras.len(), CameraTasks::default());
+        let bodies = &state.drawables.orbit_bodies;
+        for (tasks, camera) in self.cameras.iter_mut().zip(&mut state.cameras) {
+            tasks.update(camera, bodies, now);
+        }
+    }
+
+    /// Splits the window between `count` cameras, new cameras start as a copy of the active camera
+    /// & its tasks
+    pub fn split_cameras(&mut self, state: &mut State, count: usize) {
+        let active = self.active(state).clone();
+        state.split_cameras(count);
+        self.cameras.resize(state.cameras.len(), active);
+    }
+
+    /// Smallest zoom any camera is at or easing to, which curves need to be accurate enough for
+    pub fn nearest_zoom(&self, state: &State) -> f32 {
+        state.cameras.iter().enumerate()
+            .map(|(idx, camera)| match self.cameras.get(idx) {
+                Some(tasks) => tasks.nearest_zoom(camera),
+                None => camera.zoom,
+            })
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Bodies of the active camera's co-rotating frame, the frame curves are plotted in
+    pub fn corotating(&self, state: &State) -> Option<(Uuid, Uuid)> {
+        self.active(state).corotate.as_ref().map(|c| c.bodies)
     }
 
     pub fn world_affecting(&self) -> Tasks {
@@ -122,123 +416,830 @@ impl Tasks {
     }
 }
 
+/// General double/half zoom per step for fast view changes,
+/// stepping from the destination of any current zoom
+fn stepped_zoom(camera: &Camera, tasks: &CameraTasks, steps: f32) -> f32 {
+    let current_zoom = tasks.zoom.as_ref().map(|z| z.zoom_destination()).unwrap_or(camera.zoom);
+    (current_zoom * 2f32.powf(-steps)).max(MIN_ZOOM).min(MAX_ZOOM)
+}
+
 #[derive(Clone, Debug)]
 pub struct UserMouse {
-    left_down: Option<(i32, i32)>,
+    bindings: InputMap,
+    grab_down: Option<(i32, i32)>,
     last_position: (i32, i32),
-    last_left_click: Instant,
+    last_grab: Instant,
+    /// recent (time, world movement) of the current drag
+    drag_moves: Vec<(Instant, Vector2<f32>)>,
+    /// world velocity the camera keeps moving at after a drag release
+    flick: Option<Vector2<f32>>,
+    /// index of the camera the last grab dragged, which a flick keeps moving
+    grab_camera: usize,
 }
 
 impl UserMouse {
-    pub fn new() -> UserMouse {
+    pub fn new(bindings: &InputMap) -> UserMouse {
         UserMouse {
-            left_down: None,
+            bindings: bindings.clone(),
+            grab_down: None,
             last_position: (0, 0),
             // init in past sometime, to avoid optional complexity
-            last_left_click: Instant::now() - Duration::from_secs(2)
+            last_grab: Instant::now() - Duration::from_secs(2),
+            drag_moves: Vec::new(),
+            flick: None,
+            grab_camera: 0,
         }
     }
 
-    pub fn handle(&mut self, state: &mut State, _delta: f32, event: &WindowEvent, tasks: &mut Tasks) {
-        match *event {
-            WindowEvent::MouseWheel(MouseScrollDelta::LineDelta(_, y), ..) => {
-                // general double/half zoom for fast view changes
-                let mut current_zoom = state.zoom;
-                if let Some(ref zoomer) = tasks.zoom {
-                    current_zoom = zoomer.zoom_destination();
-                }
+    /// Continuous per compute loop handling, ie flick movement decaying with friction
+    /// :delta real seconds since the last update, unaffected by pause
+    pub fn update(&mut self, state: &mut State, delta: f32, tasks: &Tasks) {
+        let (camera, tasks) = match (state.cameras.get_mut(self.grab_camera), tasks.cameras.get(self.grab_camera)) {
+            (Some(camera), Some(tasks)) => (camera, tasks),
+            _ => {
+                self.flick = None; // camera has gone
+                return;
+            }
+        };
+        if tasks.zoom.is_some() || tasks.follow.is_some() || tasks.frame.is_some() || tasks.path.is_some() {
+            self.flick = None; // another camera task has taken over
+        }
+        if let Some(velocity) = self.flick.take() {
+            camera.origin += velocity * delta;
+            let velocity = velocity * (-FLICK_FRICTION * delta).exp();
+            if velocity.magnitude() > camera.zoom * 0.01 {
+                self.flick = Some(velocity);
+            }
+        }
+    }
 
-                let factor = if y < 0. { current_zoom } else { current_zoom / 2. };
-                let mut new_zoom = current_zoom - factor * y as f32;
-                if new_zoom < MIN_ZOOM {
-                    new_zoom = MIN_ZOOM;
-                }
-                else if new_zoom > MAX_ZOOM {
-                    new_zoom = MAX_ZOOM;
+    /// Returns the world velocity of recent drag movement
+    fn drag_velocity(&self) -> Option<Vector2<f32>> {
+        let recent = Duration::from_millis(FLICK_SAMPLE_MS);
+        let moves: Vec<_> = self.drag_moves.iter().filter(|&&(at, _)| at.elapsed() < recent).collect();
+        let oldest = moves.first()?.0.elapsed();
+        let seconds = oldest.as_secs() as f32 + oldest.subsec_nanos() as f32 / 1_000_000_000.0;
+        let movement = moves.iter().fold(Vector2::zero(), |sum, &&(_, m)| sum + m);
+        // consider at least a frame's worth of time to avoid huge velocities from single moves
+        Some(movement / seconds.max(1.0 / 60.0))
+    }
+
+    /// Zooms keeping the world location under the cursor fixed, continuing any current zoom
+    fn zoom_toward_cursor(&mut self, state: &mut State, tasks: &mut Tasks, steps: f32) {
+        let now = tasks.clock.wall();
+        let camera = state.camera();
+        let tasks = tasks.active_mut(state);
+        let new_zoom = stepped_zoom(camera, tasks, steps);
+        let cursor = camera.viewport.local(self.last_position);
+        let origin = Zoomer::origin_zooming_to_screen(new_zoom, cursor, camera);
+        tasks.frame = None;
+        match tasks.zoom {
+            Some(ref mut zoomer) => zoomer.retarget(new_zoom, origin, now),
+            None => tasks.zoom = Some(Zoomer::zoom_to_world(new_zoom, origin.into(), camera, now)),
+        }
+        trace!("wheel:zooming {:.2} -> {:.2} toward ({:.3},{:.3})",
+            camera.zoom, new_zoom, cursor.0, cursor.1);
+    }
+
+    pub fn handle(&mut self, state: &mut State, event: &WindowEvent, tasks: &mut Tasks) {
+        match self.bindings.event_action(event) {
+            Some((Action::Grab, true)) => {
+                if self.minimap_click(state, tasks) {
+                    return;
                 }
-                tasks.zoom = Some(Zoomer::zoom_to_screen(new_zoom, self.last_position, state));
-                debug!("wheel:zooming {:.2} -> {:.2} toward ({:.3},{:.3})",
-                    state.zoom, new_zoom, self.last_position.0, self.last_position.1);
-            }
-            WindowEvent::MouseInput(ElementState::Pressed, MouseButton::Left) => {
-                self.left_down = Some(self.last_position);
+                self.grab_down = Some(self.last_position);
+                self.grab_camera = state.active_camera;
+                self.drag_moves.clear();
+                self.flick = None;
                 // cancel any current tasks
-                tasks.zoom = None;
-                tasks.follow = None;
-                if self.last_left_click.elapsed() < Duration::from_millis(DBL_CLICK_MS) {
+                tasks.active_mut(state).cancel();
+                if self.last_grab.elapsed() < Duration::from_millis(self.bindings.double_click_ms) {
                     self.handle_double_click(state, tasks);
                 }
-                self.last_left_click = Instant::now();
+                self.last_grab = Instant::now();
             },
-            WindowEvent::MouseInput(ElementState::Released, MouseButton::Left) => {
-                if self.left_down.is_some() {
-                    debug!("left-drag {:?} -> {:?}", self.left_down.unwrap(), self.last_position);
-                    self.left_down = None;
+            Some((Action::Grab, false)) => {
+                if let Some(grab_down) = self.grab_down.take() {
+                    self.flick = self.drag_velocity();
+                    debug!("drag {:?} -> {:?}, flick {:?}", grab_down, self.last_position, self.flick);
+                    self.drag_moves.clear();
                 }
             },
+            Some((Action::FollowNearest, true)) => self.follow_nearest(state, tasks),
+            _ => (),
+        }
+
+        match *event {
+            WindowEvent::MouseWheel(delta, ..) => {
+                let steps = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => y.abs(),
+                    // trackpads send many small deltas
+                    MouseScrollDelta::PixelDelta(_, y) => y.abs() / PIXELS_PER_ZOOM_STEP,
+                };
+                match self.bindings.wheel_action(event) {
+                    Some(Action::ZoomIn) => self.zoom_toward_cursor(state, tasks, steps),
+                    Some(Action::ZoomOut) => self.zoom_toward_cursor(state, tasks, -steps),
+                    _ => (),
+                }
+            }
             WindowEvent::MouseMoved(x, y) => {
-                if self.left_down.is_some() {
-                    let movement =
-                        state.screen_to_world(self.last_position) - state.screen_to_world((x, y));
-                    state.origin += movement;
+                if self.grab_down.is_some() {
+                    let camera = state.camera_mut();
+                    let movement = camera.window_to_world(self.last_position) - camera.window_to_world((x, y));
+                    camera.origin += movement;
+
+                    let recent = Duration::from_millis(FLICK_SAMPLE_MS);
+                    self.drag_moves.retain(|&(at, _)| at.elapsed() < recent);
+                    self.drag_moves.push((Instant::now(), movement));
+                }
+                else if !(state.render_minimap && minimap::in_inset(state, (x, y))) {
+                    // input controls the camera under the cursor, the minimap controls the last one
+                    if let Some(idx) = state.camera_at((x, y)) {
+                        state.active_camera = idx;
+                    }
                 }
                 self.last_position = (x, y);
+                state.cursor = (x, y);
             },
             _ => (),
         }
     }
 
+    /// Recentres the camera on the world location under the cursor if it's on the minimap,
+    /// returns whether it was
+    fn minimap_click(&mut self, state: &State, tasks: &mut Tasks) -> bool {
+        if !state.render_minimap {
+            return false;
+        }
+        match Overview::of(state).inset_to_world(state, self.last_position) {
+            Some(world) => {
+                debug!("minimap click at {:?} => world {:?}", self.last_position, world);
+                self.flick = None;
+                let now = tasks.clock.wall();
+                let camera = state.camera();
+                let tasks = tasks.active_mut(state);
+                tasks.cancel();
+                tasks.zoom = Some(Zoomer::zoom_to_world(camera.zoom, world.into(), camera, now));
+                true
+            }
+            None => false,
+        }
+    }
+
     fn handle_double_click(&mut self, state: &mut State, tasks: &mut Tasks) {
-        let click_pos = state.screen_to_world(self.last_position);
+        let click_pos = state.camera().window_to_world(self.last_position);
         debug!("dbl click at {:?} => world {:?}", self.last_position, click_pos);
         let body = state.drawables.orbit_bodies.iter().find(|body| {
             click_pos.distance(body.center.cast()) < body.radius as f32
         });
         if let Some(body) = body {
-            info!("Following body {}", body.id);
-            tasks.zoom = Some(Zoomer::just_zoom(state.zoom, state));
-            tasks.follow = Some(body.id);
+            let now = tasks.clock.wall();
+            tasks.active_mut(state).follow_body(body, state.camera(), now);
+        }
+    }
+
+    fn follow_nearest(&mut self, state: &mut State, tasks: &mut Tasks) {
+        let cursor_pos = state.camera().window_to_world(self.last_position).cast();
+        let nearest = state.drawables.orbit_bodies.iter().min_by(|a, b| {
+            a.center.distance2(cursor_pos).partial_cmp(&b.center.distance2(cursor_pos))
+                .unwrap_or(Ordering::Equal)
+        });
+        if let Some(body) = nearest {
+            let now = tasks.clock.wall();
+            tasks.active_mut(state).follow_body(body, state.camera(), now);
         }
     }
 }
 
+/// Order in which follow next/previous cycles through bodies
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FollowOrder {
+    /// heaviest first
+    Mass,
+    /// nearest to the camera first
+    Distance,
+}
+
 #[derive(Clone, Debug)]
-pub struct UserKeys {}
+pub struct UserKeys {
+    bindings: InputMap,
+    follow_order: FollowOrder,
+    /// last body followed by cycling & the camera location distances are ordered from,
+    /// keeps distance ordering stable while the camera moves to each body
+    cycle_anchor: Option<(Uuid, Vector2<f32>)>,
+    /// currently held pan inputs
+    panning: Vec<(Trigger, Action)>,
+    /// eases the pan velocity, in screen space, toward the held pan keys direction
+    pan_easer: Option<Easer<f32>>,
+    /// clock time the pan easer started, the easer itself runs from 0 to keep f32 precision
+    pan_started: f64,
+    /// camera path played by the play path action
+    pub camera_path: Option<CameraPath>,
+}
 
 impl UserKeys {
-    pub fn new() -> UserKeys {
-        UserKeys {}
-    }
-
-    pub fn handle(&mut self, state: &mut State, _delta: f32, event: &WindowEvent, tasks: &mut Tasks) {
-        if let WindowEvent::KeyboardInput(ElementState::Pressed, _, Some(keypress), _) = *event {
-            let body = match keypress {
-                VirtualKeyCode::Home => state.drawables.orbit_bodies.iter()
-                    .max_by_key(|x| x.mass.round() as i64),
-                VirtualKeyCode::Key1 => state.drawables.orbit_bodies.get(0),
-                VirtualKeyCode::Key2 => state.drawables.orbit_bodies.get(1),
-                VirtualKeyCode::Key3 => state.drawables.orbit_bodies.get(2),
-                VirtualKeyCode::Key4 => state.drawables.orbit_bodies.get(3),
-                VirtualKeyCode::Key5 => state.drawables.orbit_bodies.get(4),
-                VirtualKeyCode::Key6 => state.drawables.orbit_bodies.get(5),
-                VirtualKeyCode::Key7 => state.drawables.orbit_bodies.get(6),
-                VirtualKeyCode::Key8 => state.drawables.orbit_bodies.get(7),
-                VirtualKeyCode::Key9 => state.drawables.orbit_bodies.get(8),
-                VirtualKeyCode::Key0 => state.drawables.orbit_bodies.get(9),
-                _ => None
-            };
-            if let Some(body) = body {
-                tasks.follow = None;
-                tasks.zoom = Some(Zoomer::zoom_to_world(state.zoom,
-                                                        body.center.cast().into(),
-                                                        state));
-            }
-            if keypress == VirtualKeyCode::P {
-                state.pause = !state.pause;
-            }
-            if keypress == VirtualKeyCode::C {
-                state.render_curves = !state.render_curves;
+    pub fn new(bindings: &InputMap) -> UserKeys {
+        UserKeys {
+            bindings: bindings.clone(),
+            follow_order: FollowOrder::Mass,
+            cycle_anchor: None,
+            panning: Vec::new(),
+            pan_easer: None,
+            pan_started: 0.0,
+            camera_path: None,
+        }
+    }
+
+    fn pan_direction(action: Action) -> Option<Vector2<f32>> {
+        match action {
+            Action::PanUp => Some(Vector2::new(0.0, 1.0)),
+            Action::PanDown => Some(Vector2::new(0.0, -1.0)),
+            Action::PanLeft => Some(Vector2::new(-1.0, 0.0)),
+            Action::PanRight => Some(Vector2::new(1.0, 0.0)),
+            _ => None,
+        }
+    }
+
+    /// Pan easer time at a clock time
+    fn pan_time(&self, now: f64) -> f32 {
+        (now - self.pan_started) as f32
+    }
+
+    fn pan_velocity(&self, now: f64) -> Vector2<f32> {
+        match self.pan_easer {
+            Some(ref easer) => {
+                let vals = easer.values_at(self.pan_time(now));
+                Vector2::new(vals[0], vals[1])
+            }
+            None => Vector2::zero(),
+        }
+    }
+
+    /// Tracks pan input presses & releases, returns true if the event was handled
+    fn handle_pan(&mut self, state: &State, event: &WindowEvent, tasks: &mut Tasks) -> bool {
+        let trigger = match Trigger::of(event) {
+            Some(trigger) => trigger,
+            None => return false,
+        };
+        let held = self.panning.iter().any(|&(t, _)| t == trigger);
+
+        match self.bindings.event_action(event) {
+            Some((action, true)) if UserKeys::pan_direction(action).is_some() => {
+                if held {
+                    return true; // key repeat
+                }
+                self.panning.push((trigger, action));
+                // panning takes over the camera
+                tasks.active_mut(state).cancel();
+            }
+            Some((_, false)) | None if held => self.panning.retain(|&(t, _)| t != trigger),
+            _ => return false,
+        }
+
+        let direction = self.panning.iter()
+            .filter_map(|&(_, action)| UserKeys::pan_direction(action))
+            .fold(Vector2::zero(), |sum: Vector2<f32>, d| sum + d);
+        let target = if direction.is_zero() { direction } else { direction.normalize() };
+        let now = tasks.clock.wall();
+        let current = self.pan_velocity(now);
+        self.pan_started = now;
+        self.pan_easer = Some(Easer::using(Quad::ease_in_out)
+            .duration(KEY_PAN_EASE_SECONDS)
+            .add_transition(current.x, target.x)
+            .add_transition(current.y, target.y));
+        true
+    }
+
+    /// Continuous per compute loop input handling, ie panning while keys are held
+    /// :delta real seconds since the last update, unaffected by pause
+    pub fn update(&mut self, state: &mut State, delta: f32, tasks: &Tasks) {
+        let now = tasks.clock.wall();
+        let velocity = self.pan_velocity(now);
+        let pan_time = self.pan_time(now);
+        if self.panning.is_empty() && self.pan_easer.as_ref().map(|e| e.has_finished(pan_time)).unwrap_or(false) {
+            self.pan_easer = None;
+        }
+        if !velocity.is_zero() {
+            // pan relative to the screen, covering more world when zoomed out
+            let camera = state.camera_mut();
+            camera.origin += rotate(velocity, camera.rotation) * camera.zoom * KEY_PAN_SPEED * delta;
+        }
+    }
+
+    /// Zooms toward the screen center, keeping any current zoom's origin movement
+    fn zoom_step(&mut self, state: &State, tasks: &mut Tasks, steps: f32) {
+        let now = tasks.clock.wall();
+        let camera = state.camera();
+        let tasks = tasks.active_mut(state);
+        let new_zoom = stepped_zoom(camera, tasks, steps);
+        tasks.frame = None;
+        match tasks.zoom {
+            Some(ref mut zoomer) => zoomer.update_zoom_destination(new_zoom, now),
+            None => tasks.zoom = Some(Zoomer::just_zoom(new_zoom, camera, now)),
+        }
+        debug!("key:zooming {:.2} -> {:.2}", camera.zoom, new_zoom);
+    }
+
+    /// Follows the next (or previous) body in the current follow order
+    fn cycle_follow(&mut self, state: &State, tasks: &mut Tasks, forwards: bool) {
+        let now = tasks.clock.wall();
+        let camera = state.camera();
+        let tasks = tasks.active_mut(state);
+        let anchor = match self.cycle_anchor {
+            Some((id, anchor)) if tasks.follow == Some(id) => anchor,
+            _ => camera.origin,
+        };
+
+        let mut bodies: Vec<&OrbitBody> = state.drawables.orbit_bodies.iter().collect();
+        if bodies.is_empty() {
+            return;
+        }
+        match self.follow_order {
+            FollowOrder::Mass => bodies.sort_by(|a, b| {
+                b.mass.partial_cmp(&a.mass).unwrap_or(Ordering::Equal)
+            }),
+            FollowOrder::Distance => {
+                let anchor = anchor.cast();
+                bodies.sort_by(|a, b| {
+                    a.center.distance2(anchor).partial_cmp(&b.center.distance2(anchor))
+                        .unwrap_or(Ordering::Equal)
+                })
+            }
+        }
+
+        let len = bodies.len();
+        let next = match tasks.follow.and_then(|id| bodies.iter().position(|b| b.id == id)) {
+            Some(idx) if forwards => (idx + 1) % len,
+            Some(idx) => (idx + len - 1) % len,
+            None if forwards => 0,
+            None => len - 1,
+        };
+        tasks.follow_body(bodies[next], camera, now);
+        self.cycle_anchor = Some((bodies[next].id, anchor));
+    }
+
+    /// Toggles the active camera co-rotating with the followed body & the heaviest other body,
+    /// or the two heaviest bodies when not following
+    fn toggle_corotation(&mut self, state: &mut State, tasks: &mut Tasks) {
+        if tasks.active_mut(state).corotate.take().is_some() {
+            let camera = state.camera_mut();
+            camera.rotation = 0.0;
+            camera.frame = None;
+            info!("Co-rotating view off");
+            return;
+        }
+
+        let mut bodies: Vec<&OrbitBody> = state.drawables.orbit_bodies.iter().collect();
+        bodies.sort_by(|a, b| b.mass.partial_cmp(&a.mass).unwrap_or(Ordering::Equal));
+        let pair = match tasks.active(state).follow {
+            Some(id) => bodies.iter().find(|b| b.id != id).map(|heaviest| (heaviest.id, id)),
+            None if bodies.len() > 1 => Some((bodies[0].id, bodies[1].id)),
+            None => None,
+        };
+        if let Some((a, b)) = pair {
+            let name = |id| bodies.iter().find(|b| b.id == id).map(|b| b.display_name()).unwrap_or_default();
+            info!("Co-rotating view with {} & {}", name(a), name(b));
+            tasks.active_mut(state).corotate = Some(CoRotation::new(a, b));
+        }
+    }
+
+    pub fn handle(&mut self, state: &mut State, event: &WindowEvent, tasks: &mut Tasks) {
+        if self.handle_pan(state, event, tasks) {
+            return;
+        }
+        let action = match self.bindings.event_action(event) {
+            Some((action, true)) => action,
+            _ => return,
+        };
+
+        let body = match action {
+            Action::FollowHeaviest => state.drawables.orbit_bodies.iter()
+                .max_by_key(|x| x.mass.round() as i64),
+            Action::Follow(idx) => state.drawables.orbit_bodies.get(idx as usize),
+            _ => None,
+        };
+        if let Some(body) = body {
+            let now = tasks.clock.wall();
+            let camera = state.camera();
+            let tasks = tasks.active_mut(state);
+            tasks.cancel();
+            tasks.zoom = Some(Zoomer::zoom_to_world(camera.zoom, body.center.cast().into(), camera, now));
+        }
+
+        match action {
+            Action::ZoomIn => self.zoom_step(state, tasks, 1.0),
+            Action::ZoomOut => self.zoom_step(state, tasks, -1.0),
+            Action::FollowNext => self.cycle_follow(state, tasks, true),
+            Action::FollowPrevious => self.cycle_follow(state, tasks, false),
+            Action::AutoFrame | Action::AutoFrameVisible if tasks.active(state).frame.is_some() => {
+                tasks.active_mut(state).frame = None;
+            }
+            Action::AutoFrame => tasks.active_mut(state).auto_frame(AutoFrame::all()),
+            Action::AutoFrameVisible => {
+                let (min, max) = state.camera().visible_world_range();
+                let visible = state.drawables.orbit_bodies.iter()
+                    .filter(|b| {
+                        let c: Vector2<f32> = b.center.cast();
+                        c.x >= min.x && c.x <= max.x && c.y >= min.y && c.y <= max.y
+                    })
+                    .map(|b| b.id)
+                    .collect();
+                tasks.active_mut(state).auto_frame(AutoFrame::bodies(visible));
+            }
+            Action::CoRotate => self.toggle_corotation(state, tasks),
+            Action::ToggleFollowOrder => {
+                self.follow_order = match self.follow_order {
+                    FollowOrder::Mass => FollowOrder::Distance,
+                    FollowOrder::Distance => FollowOrder::Mass,
+                };
+                self.cycle_anchor = None;
+                info!("Follow order: {:?}", self.follow_order);
             }
+            Action::Pause => state.pause = !state.pause,
+            Action::ToggleCurves => state.render_curves = !state.render_curves,
+            Action::ToggleTrails => state.render_trails = !state.render_trails,
+            Action::ToggleGrid => state.render_grid = !state.render_grid,
+            Action::CycleField => {
+                state.field_mode = state.field_mode.next();
+                info!("Field overlay: {:?}", state.field_mode);
+            }
+            Action::ToggleContours => state.field_contours = !state.field_contours,
+            Action::ToggleArrows => state.render_arrows = !state.render_arrows,
+            Action::ToggleLabels => state.render_labels = !state.render_labels,
+            Action::ToggleDebug => state.show_debug = !state.show_debug,
+            Action::ToggleMinimap => state.render_minimap = !state.render_minimap,
+            Action::ToggleBloom => state.render_bloom = !state.render_bloom,
+            Action::ToggleArrowScale => {
+                state.arrow_scale = state.arrow_scale.toggled();
+                info!("Arrow scale: {:?}", state.arrow_scale);
+            }
+            Action::ShowBindings => state.show_bindings = !state.show_bindings,
+            Action::PlayPath => match tasks.active_mut(state).path {
+                Some(ref mut playback) => {
+                    playback.paused = !playback.paused;
+                    info!("Camera path {}", if playback.paused { "paused" } else { "resumed" });
+                }
+                None => if let Some(ref path) = self.camera_path {
+                    tasks.active_mut(state).play_path(path.clone(), state.camera());
+                },
+            },
+            Action::CycleSplit => {
+                let count = match state.cameras.len() {
+                    1 => 2,
+                    2 => 4,
+                    _ => 1,
+                };
+                tasks.split_cameras(state, count);
+                info!("{} viewport{}", count, if count == 1 { "" } else { "s" });
+            }
+            Action::StopPath => if tasks.active_mut(state).path.take().is_some() {
+                info!("Camera path stopped");
+            },
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod input_test {
+    use super::*;
+
+    fn body(x: f64, y: f64, radius: f64, mass: f64) -> OrbitBody {
+        OrbitBody::new((x, y).into(), (0.0, 0.0).into(), mass, radius)
+    }
+
+    fn state_with(bodies: Vec<OrbitBody>) -> State {
+        let mut state = State::new(200, 100);
+        state.drawables.orbit_bodies = bodies;
+        state
+    }
+
+    #[test]
+    fn auto_frame_fits_all_bodies() {
+        let state = state_with(vec!(body(-10.0, 0.0, 1.0, 10.0), body(10.0, 4.0, 1.0, 10.0)));
+        let frame = AutoFrame { padding: 0.0, ..AutoFrame::all() };
+
+        let (zoom, origin) = frame.target(&state.drawables.orbit_bodies, state.camera()).unwrap();
+        assert_eq!(origin, Vector2::new(0.0, 2.0));
+        // width 22 over aspect ratio 2 dominates height 6
+        assert_eq!(zoom, 5.5);
+    }
+
+    #[test]
+    fn auto_frame_ignores_outliers() {
+        let state = state_with(vec!(
+            body(-2.0, 0.0, 1.0, 10.0),
+            body(2.0, 0.0, 1.0, 10.0),
+            body(5000.0, 0.0, 1.0, 1.0)));
+        let frame = AutoFrame { padding: 0.0, ..AutoFrame::all() };
+
+        let (zoom, origin) = frame.target(&state.drawables.orbit_bodies, state.camera()).unwrap();
+        assert!(origin.distance(Vector2::new(0.0, 0.0)) < 0.0001);
+        assert_eq!(zoom, MIN_ZOOM.max(1.5));
+    }
+
+    #[test]
+    fn auto_frame_subset_respects_zoom_limits() {
+        let bodies = vec!(body(0.0, 0.0, 0.1, 10.0), body(1000.0, 0.0, 0.1, 10.0));
+        let first = bodies[0].id;
+        let state = state_with(bodies);
+
+        let (zoom, origin) = AutoFrame::bodies(vec!(first)).target(&state.drawables.orbit_bodies, state.camera()).unwrap();
+        assert_eq!(origin, Vector2::new(0.0, 0.0));
+        assert_eq!(zoom, MIN_ZOOM);
+
+        let frame = AutoFrame { outlier_distance: 1./0., ..AutoFrame::all() };
+        assert_eq!(frame.target(&state.drawables.orbit_bodies, state.camera()).unwrap().0, MAX_ZOOM);
+
+        assert!(AutoFrame::bodies(vec!()).target(&state.drawables.orbit_bodies, state.camera()).is_none());
+    }
+
+    #[test]
+    fn auto_frame_tracks_drift_without_restarting_eases() {
+        let mut state = state_with(vec!(body(-10.0, 0.0, 1.0, 10.0), body(10.0, 4.0, 1.0, 10.0)));
+        let (zoom, origin) = AutoFrame::all().target(&state.drawables.orbit_bodies, state.camera()).unwrap();
+        state.camera_mut().zoom = zoom;
+        state.camera_mut().origin = origin;
+        let mut tasks = Tasks::new();
+        tasks.cameras[0].auto_frame(AutoFrame::all());
+
+        state.drawables.orbit_bodies[1].center.x += 0.01;
+        tasks.update(&mut state);
+        assert!(tasks.cameras[0].zoom.is_none());
+        let target = AutoFrame::all().target(&state.drawables.orbit_bodies, state.camera()).unwrap();
+        assert_eq!((state.camera().zoom, state.camera().origin), target);
+
+        // a large change eases over
+        state.drawables.orbit_bodies[1].center.x += 20.0;
+        tasks.update(&mut state);
+        assert!(tasks.cameras[0].zoom.is_some());
+    }
+
+    #[test]
+    fn corotation_moves_camera_with_frame() {
+        let mut state = state_with(vec!(body(-1.0, 0.0, 0.1, 10.0), body(1.0, 0.0, 0.1, 10.0)));
+        state.camera_mut().origin = Vector2::new(0.0, 1.0);
+        let (a, b) = (state.drawables.orbit_bodies[0].id, state.drawables.orbit_bodies[1].id);
+        let mut corotation = CoRotation::new(a, b);
+
+        assert!(corotation.update(&mut state.cameras[0], &state.drawables.orbit_bodies));
+        assert_eq!(state.camera().rotation, 0.0);
+        assert!(state.camera().origin.distance(Vector2::new(0.0, 1.0)) < 0.0001);
+
+        // pair turns a quarter anti-clockwise & drifts right
+        state.drawables.orbit_bodies[0].center = (5.0, -1.0).into();
+        state.drawables.orbit_bodies[1].center = (5.0, 1.0).into();
+        assert!(corotation.update(&mut state.cameras[0], &state.drawables.orbit_bodies));
+        assert!((state.camera().rotation - ::std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+        assert!(state.camera().origin.distance(Vector2::new(4.0, 0.0)) < 0.0001, "{:?}", state.camera().origin);
+
+        state.drawables.orbit_bodies.pop();
+        assert!(!corotation.update(&mut state.cameras[0], &state.drawables.orbit_bodies));
+    }
+
+    #[test]
+    fn corotation_is_per_camera() {
+        let mut state = state_with(vec!(body(0.0, -1.0, 0.1, 10.0), body(0.0, 1.0, 0.1, 10.0)));
+        let (a, b) = (state.drawables.orbit_bodies[0].id, state.drawables.orbit_bodies[1].id);
+        let mut tasks = Tasks::with_clock(Clock::manual());
+        tasks.split_cameras(&mut state, 2);
+        tasks.cameras[1].corotate = Some(CoRotation::new(a, b));
+
+        tasks.update(&mut state);
+        assert_eq!(state.cameras[0].rotation, 0.0);
+        assert!(state.cameras[0].frame.is_none());
+        assert!((state.cameras[1].rotation - ::std::f32::consts::FRAC_PI_2).abs() < 0.0001);
+        assert_eq!(state.cameras[1].frame.map(|f| f.bodies), Some((a, b)));
+
+        // curves are plotted in the active camera's frame
+        assert_eq!(tasks.corotating(&state), None);
+        state.active_camera = 1;
+        assert_eq!(tasks.corotating(&state), Some((a, b)));
+    }
+
+    #[test]
+    fn follow_eases_to_body_with_clock() {
+        let mut state = state_with(vec!(body(10.0, 0.0, 1.0, 10.0)));
+        let mut tasks = Tasks::with_clock(Clock::manual());
+        tasks.clock.advance(5_000_000.0); // long running, precision must hold
+        let target = state.drawables.orbit_bodies[0].clone();
+        tasks.cameras[0].follow_body(&target, state.camera(), tasks.clock.wall());
+
+        tasks.update(&mut state);
+        assert_eq!(state.camera().origin.x, 0.0);
+        tasks.clock.advance(0.05);
+        tasks.update(&mut state);
+        let early = state.camera().origin.x;
+        assert!(early > 0.0 && early < 5.0, "{}", early);
+
+        tasks.clock.advance(f64::from(ZOOM_DURATION_SECONDS) / 2.0);
+        tasks.update(&mut state);
+        assert!(state.camera().origin.x > 9.0 && state.camera().origin.x < 10.0, "{}", state.camera().origin.x);
+
+        tasks.clock.advance(f64::from(ZOOM_DURATION_SECONDS));
+        tasks.update(&mut state);
+        assert_eq!(state.camera().origin.x, 10.0);
+        assert!(tasks.cameras[0].zoom.is_none());
+        assert_eq!(tasks.cameras[0].follow, Some(target.id));
+        assert_eq!(state.camera().following, Some(target.id));
+    }
+
+    fn key(key_state: ElementState, key: VirtualKeyCode) -> WindowEvent {
+        WindowEvent::KeyboardInput(key_state, 0, Some(key), Default::default())
+    }
+
+    fn shift_key(key_state: ElementState, key: VirtualKeyCode) -> WindowEvent {
+        let mut event = WindowEvent::KeyboardInput(key_state, 0, Some(key), Default::default());
+        if let WindowEvent::KeyboardInput(_, _, _, ref mut modifiers) = event {
+            modifiers.shift = true;
+        }
+        event
+    }
+
+    #[test]
+    fn key_pan_eases_with_zoom() {
+        let mut state = state_with(vec!());
+        state.camera_mut().zoom = 2.0;
+        let mut tasks = Tasks::with_clock(Clock::manual());
+        tasks.clock.advance(5_000_000.0); // long running, pan easing must still progress
+        let mut keys = UserKeys::new(&InputMap::default());
+        let update = |keys: &mut UserKeys, state: &mut State, tasks: &mut Tasks, delta: f32| {
+            tasks.clock.advance(f64::from(delta));
+            keys.update(state, delta, tasks);
+        };
+
+        keys.handle(&mut state, &key(ElementState::Pressed, VirtualKeyCode::Right), &mut tasks);
+        update(&mut keys, &mut state, &mut tasks, KEY_PAN_EASE_SECONDS / 2.0);
+        let eased_x = state.camera().origin.x;
+        assert!(eased_x > 0.0 && eased_x < KEY_PAN_SPEED * 2.0 * KEY_PAN_EASE_SECONDS / 2.0);
+
+        update(&mut keys, &mut state, &mut tasks, KEY_PAN_EASE_SECONDS);
+        update(&mut keys, &mut state, &mut tasks, 1.0);
+        assert!((state.camera().origin.x - eased_x - KEY_PAN_SPEED * 2.0 * (1.0 + KEY_PAN_EASE_SECONDS)).abs() < 0.0001);
+        assert_eq!(state.camera().origin.y, 0.0);
+
+        keys.handle(&mut state, &key(ElementState::Released, VirtualKeyCode::Right), &mut tasks);
+        update(&mut keys, &mut state, &mut tasks, KEY_PAN_EASE_SECONDS * 2.0);
+        let stopped_x = state.camera().origin.x;
+        update(&mut keys, &mut state, &mut tasks, 1.0);
+        assert_eq!(state.camera().origin.x, stopped_x);
+    }
+
+    #[test]
+    fn cycle_follow_by_mass() {
+        let mut state = state_with(vec!(
+            body(0.0, 0.0, 1.0, 5.0),
+            body(10.0, 0.0, 1.0, 20.0),
+            body(-30.0, 0.0, 1.0, 10.0)));
+        let ids: Vec<Uuid> = state.drawables.orbit_bodies.iter().map(|b| b.id).collect();
+        let mut tasks = Tasks::new();
+        let mut keys = UserKeys::new(&InputMap::default());
+        let mut press = |event: WindowEvent| {
+            keys.handle(&mut state, &event, &mut tasks);
+            tasks.cameras[0].follow
+        };
+        let tab = || key(ElementState::Pressed, VirtualKeyCode::Tab);
+
+        // heaviest first, wrapping around after the lightest
+        assert_eq!(press(tab()), Some(ids[1]));
+        assert_eq!(press(tab()), Some(ids[2]));
+        assert_eq!(press(tab()), Some(ids[0]));
+        assert_eq!(press(tab()), Some(ids[1]));
+
+        // shift reverses, wrapping back to the lightest
+        assert_eq!(press(shift_key(ElementState::Pressed, VirtualKeyCode::Tab)), Some(ids[0]));
+        assert_eq!(press(shift_key(ElementState::Pressed, VirtualKeyCode::Tab)), Some(ids[2]));
+    }
+
+    #[test]
+    fn follow_order_toggles_to_distance() {
+        let mut state = state_with(vec!(
+            body(-30.0, 0.0, 1.0, 20.0),
+            body(1.0, 0.0, 1.0, 5.0),
+            body(10.0, 0.0, 1.0, 10.0)));
+        let ids: Vec<Uuid> = state.drawables.orbit_bodies.iter().map(|b| b.id).collect();
+        let mut tasks = Tasks::new();
+        let mut keys = UserKeys::new(&InputMap::default());
+        let mut press = |event: WindowEvent| {
+            keys.handle(&mut state, &event, &mut tasks);
+            tasks.cameras[0].follow
+        };
+        let tab = || key(ElementState::Pressed, VirtualKeyCode::Tab);
+
+        // nearest to the camera first, ordered from where cycling started
+        press(key(ElementState::Pressed, VirtualKeyCode::O));
+        assert_eq!(press(tab()), Some(ids[1]));
+        assert_eq!(press(tab()), Some(ids[2]));
+        assert_eq!(press(tab()), Some(ids[0]));
+        assert_eq!(press(tab()), Some(ids[1]));
+
+        // back to mass order, the lightest wraps around to the heaviest
+        press(key(ElementState::Pressed, VirtualKeyCode::O));
+        assert_eq!(press(tab()), Some(ids[0]));
+    }
+
+    #[test]
+    fn middle_click_follows_nearest_body() {
+        let mut state = state_with(vec!());
+        state.render_minimap = false;
+        let cursor = state.camera().window_to_world((180, 20)).cast();
+        state.drawables.orbit_bodies = vec!(
+            body(0.0, 0.0, 1.0, 100.0),
+            body(cursor.x + 0.5, cursor.y, 0.1, 1.0),
+            body(-cursor.x, cursor.y, 0.1, 1.0));
+        let mut tasks = Tasks::new();
+        let mut mouse = UserMouse::new(&InputMap::default());
+
+        mouse.handle(&mut state, &WindowEvent::MouseMoved(180, 20), &mut tasks);
+        mouse.handle(&mut state, &WindowEvent::MouseInput(ElementState::Pressed, MouseButton::Middle), &mut tasks);
+        assert_eq!(tasks.cameras[0].follow, Some(state.drawables.orbit_bodies[1].id));
+    }
+
+    #[test]
+    fn stepped_zoom_doubles_and_halves() {
+        let mut state = state_with(vec!());
+        state.camera_mut().zoom = 4.0;
+        let tasks = Tasks::new();
+        assert_eq!(stepped_zoom(state.camera(), &tasks.cameras[0], 1.0), 2.0);
+        assert_eq!(stepped_zoom(state.camera(), &tasks.cameras[0], -1.0), 8.0);
+        assert_eq!(stepped_zoom(state.camera(), &tasks.cameras[0], 2.0), 1.0);
+        assert_eq!(stepped_zoom(state.camera(), &tasks.cameras[0], -10.0), MAX_ZOOM);
+    }
+
+    #[test]
+    fn zoom_retarget_continues_velocity() {
+        let mut state = state_with(vec!());
+        state.camera_mut().zoom = 32.0;
+        let mut zoomer = Zoomer::zoom_to_world(16.0, (10.0, -5.0), state.camera(), 1000.0);
+        let now = 1000.2;
+        let (zoom_before, origin_before) = (zoomer.zoom_at(now), zoomer.origin_at(now));
+        let velocities_before = zoomer.easer.velocities_at(0.2);
+
+        zoomer.retarget(8.0, Vector2::new(20.0, 0.0), now);
+        assert!((zoomer.zoom_at(now) - zoom_before).abs() < 0.001);
+        assert!((zoomer.origin_at(now) - origin_before).magnitude() < 0.001);
+        // zoom & origin both continue at their velocities
+        let velocities_after = zoomer.easer.velocities_at(0.0);
+        for (before, after) in velocities_before.iter().zip(&velocities_after) {
+            assert!((after - before).abs() < before.abs() * 0.01, "{:?} -> {:?}", velocities_before, velocities_after);
+        }
+        assert_eq!(zoomer.zoom_destination(), 8.0);
+    }
+
+    #[test]
+    fn flick_decays() {
+        let mut state = state_with(vec!());
+        let mut mouse = UserMouse::new(&InputMap::default());
+        mouse.flick = Some(Vector2::new(10.0, 0.0));
+
+        mouse.update(&mut state, 0.1, &Tasks::new());
+        assert_eq!(state.camera().origin.x, 1.0);
+        for _ in 0..100 {
+            mouse.update(&mut state, 0.1, &Tasks::new());
         }
+        assert!(mouse.flick.is_none());
+        // total distance approaches the integral of the decaying velocity, v / friction
+        assert!(state.camera().origin.x > 2.0 && state.camera().origin.x < 1.0 + 10.0 / FLICK_FRICTION);
+
+        mouse.flick = Some(Vector2::new(10.0, 0.0));
+        let mut tasks = Tasks::new();
+        tasks.cameras[0].auto_frame(AutoFrame::all());
+        mouse.update(&mut state, 0.1, &tasks);
+        assert!(mouse.flick.is_none());
+    }
+
+    #[test]
+    fn grab_stops_path() {
+        let mut state = state_with(vec!());
+        state.render_minimap = false;
+        let mut tasks = Tasks::new();
+        let path = CameraPath::parse("origin=10,0 zoom=4 duration=5").unwrap();
+        tasks.cameras[0].play_path(path, state.camera());
+        tasks.update(&mut state);
+        assert!(tasks.cameras[0].path.is_some());
+
+        let mut mouse = UserMouse::new(&InputMap::default());
+        mouse.handle(&mut state, &WindowEvent::MouseMoved(50, 50), &mut tasks);
+        mouse.handle(&mut state, &WindowEvent::MouseInput(ElementState::Pressed, MouseButton::Left), &mut tasks);
+        assert!(tasks.cameras[0].path.is_none());
+        let origin = state.camera().origin;
+        tasks.update(&mut state);
+        assert_eq!(state.camera().origin, origin);
+    }
+
+    #[test]
+    fn wheel_zooms_camera_under_cursor() {
+        let mut state = state_with(vec!());
+        state.render_minimap = false;
+        let mut tasks = Tasks::new();
+        tasks.split_cameras(&mut state, 2);
+        let mut mouse = UserMouse::new(&InputMap::default());
+
+        mouse.handle(&mut state, &WindowEvent::MouseMoved(150, 50), &mut tasks);
+        assert_eq!(state.active_camera, 1);
+        mouse.handle(&mut state, &WindowEvent::MouseWheel(MouseScrollDelta::LineDelta(0.0, 1.0),
+                                                              TouchPhase::Moved), &mut tasks);
+        assert!(tasks.cameras[0].zoom.is_none());
+        assert!(tasks.cameras[1].zoom.is_some());
     }
 }
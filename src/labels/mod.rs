@@ -0,0 +1,121 @@
+//! Body name labels, placed beside bodies in screen space
+pub mod render;
+
+use cgmath::*;
+use orbitbody::OrbitBody;
+use camera::Camera;
+use state::State;
+use std::cmp::Ordering;
+
+/// Bodies smaller than this many pixels in radius aren't labelled
+pub const MIN_LABEL_RADIUS_PIXELS: f32 = 2.0;
+/// Approximate label glyph size, for avoiding overlapping labels
+const CHAR_WIDTH_PIXELS: i32 = 7;
+const LINE_HEIGHT_PIXELS: i32 = 14;
+/// Gap between a body's edge & its label
+const OFFSET_PIXELS: f32 = 4.0;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub text: String,
+    /// bottom-left screen pixel of the text
+    pub position: [i32; 2],
+}
+
+impl Label {
+    /// Screen rectangle (min, max) the label roughly covers
+    fn bounds(&self) -> ([i32; 2], [i32; 2]) {
+        let width = self.text.chars().count() as i32 * CHAR_WIDTH_PIXELS;
+        ([self.position[0], self.position[1] - LINE_HEIGHT_PIXELS], [self.position[0] + width, self.position[1]])
+    }
+
+    fn overlaps(&self, other: &Label) -> bool {
+        let ((a_min, a_max), (b_min, b_max)) = (self.bounds(), other.bounds());
+        a_min[0] < b_max[0] && b_min[0] < a_max[0] && a_min[1] < b_max[1] && b_min[1] < a_max[1]
+    }
+}
+
+/// Labels for named bodies on screen, bigger bodies first. Bodies too small, overlapping a bigger
+/// labelled body or whose label would overlap another label go unlabelled
+pub fn visible_labels(state: &State, camera: &Camera) -> Vec<Label> {
+    let pixels_per_world = 1.0 / camera.world_per_pixel();
+    let mut bodies: Vec<(&OrbitBody, Vector2<f32>, f32)> = state.drawables.orbit_bodies.iter()
+        .filter(|body| body.name.is_some())
+        .map(|body| (body, camera.world_to_screen(body.center.cast()), body.radius as f32 * pixels_per_world))
+        .filter(|&(_, screen, radius)| radius >= MIN_LABEL_RADIUS_PIXELS &&
+            screen.x + radius >= 0.0 && screen.x - radius <= camera.viewport.width as f32 &&
+            screen.y + radius >= 0.0 && screen.y - radius <= camera.viewport.height as f32)
+        .collect();
+    bodies.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(Ordering::Equal));
+
+    let mut labelled: Vec<(Vector2<f32>, f32)> = Vec::new();
+    let mut labels: Vec<Label> = Vec::new();
+    for (body, screen, radius) in bodies {
+        if labelled.iter().any(|&(other, other_radius)| other.distance(screen) < radius + other_radius) {
+            continue;
+        }
+        // up & right of the body, at 45°
+        let offset = radius * 0.7 + OFFSET_PIXELS;
+        let label = Label {
+            text: body.display_name(),
+            position: [(screen.x + offset).round() as i32, (screen.y - offset).round() as i32],
+        };
+        if labels.iter().any(|other| other.overlaps(&label)) {
+            continue;
+        }
+        labelled.push((screen, radius));
+        labels.push(label);
+    }
+    labels
+}
+
+#[cfg(test)]
+mod labels_test {
+    use super::*;
+
+    fn state() -> State {
+        // 0.1 world per pixel
+        let mut state = State::new(400, 400);
+        state.camera_mut().zoom = 20.0;
+        state
+    }
+
+    #[test]
+    fn labels_beside_bodies() {
+        let mut state = state();
+        state.drawables.orbit_bodies.truncate(1);
+        state.drawables.orbit_bodies[0].center = (0.0, 0.0).into();
+        state.drawables.orbit_bodies[0].radius = 1.0;
+
+        let labels = visible_labels(&state, state.camera());
+        assert_eq!(labels, vec!(Label { text: "Alpha".into(), position: [211, 189] }));
+
+        state.drawables.orbit_bodies[0].name = None;
+        assert!(visible_labels(&state, state.camera()).is_empty());
+    }
+
+    #[test]
+    fn hides_small_and_overlapping() {
+        let mut state = state();
+        {
+            let bodies = &mut state.drawables.orbit_bodies;
+            bodies[0].center = (0.0, 0.0).into();
+            bodies[0].radius = 2.0;
+            // overlaps the bigger first body
+            bodies[1].center = (2.5, 0.0).into();
+            bodies[1].radius = 1.0;
+            // too small, under 2 pixels
+            bodies[2].center = (10.0, -10.0).into();
+            bodies[2].radius = 0.15;
+            // apart, but its label would overlap the first body's label
+            bodies[3].center = (4.0, 1.0).into();
+            bodies[3].radius = 0.5;
+        }
+        let labels = visible_labels(&state, state.camera());
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].text, "Alpha");
+
+        state.drawables.orbit_bodies[3].center = (-15.0, 15.0).into();
+        assert_eq!(visible_labels(&state, state.camera()).len(), 2);
+    }
+}
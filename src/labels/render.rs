@@ -0,0 +1,32 @@
+use super::*;
+use gfx::*;
+use gfx_text::{HorizontalAnchor, VerticalAnchor};
+use gfx_text;
+
+const LABEL_COLOR: [f32; 4] = [0.85, 0.85, 0.85, 0.9];
+
+pub struct LabelBrush<R: Resources, F: Factory<R>> {
+    renderer: gfx_text::Renderer<R, F>,
+}
+
+impl<R: Resources, F: Factory<R> + Clone> LabelBrush<R, F> {
+    pub fn new(factory: &F) -> LabelBrush<R, F> {
+        let renderer = gfx_text::new(factory.clone()).with_size(13).unwrap();
+        LabelBrush { renderer }
+    }
+
+    pub fn draw<C: CommandBuffer<R>, T: format::RenderFormat>(&mut self,
+                                                             encoder: &mut Encoder<R, C>,
+                                                             target: &handle::RenderTargetView<R, T>,
+                                                             state: &State,
+                                                             camera: &Camera)
+                                                             -> Result<(), gfx_text::Error>
+    {
+        for label in visible_labels(state, camera) {
+            self.renderer.add_anchored(&label.text, label.position,
+                                       HorizontalAnchor::Left, VerticalAnchor::Bottom,
+                                       LABEL_COLOR);
+        }
+        self.renderer.draw(encoder, target)
+    }
+}
@@ -0,0 +1,81 @@
+#![cfg_attr(feature = "bench", feature(test))]
+#[cfg(feature = "bench")]
+extern crate test;
+
+#[macro_use] extern crate log;
+#[macro_use] extern crate gfx;
+#[macro_use] extern crate gfx_macros;
+#[macro_use] extern crate gfx_shader_watch;
+extern crate glutin;
+extern crate time;
+extern crate image;
+extern crate cgmath;
+extern crate gfx_text;
+extern crate gfx_device_gl;
+extern crate gfx_gl;
+extern crate easer;
+extern crate num;
+extern crate uuid;
+extern crate rayon;
+extern crate single_value_channel;
+
+pub mod input;
+pub mod state;
+pub mod background;
+pub mod orbitbody;
+pub mod ease;
+pub mod compute;
+pub mod debug;
+pub mod orbitcurve;
+pub mod seer;
+pub mod scenario;
+pub mod bindings;
+pub mod camerapath;
+pub mod capture;
+pub mod clock;
+pub mod grid;
+pub mod field;
+pub mod arrows;
+pub mod labels;
+pub mod minimap;
+pub mod camera;
+pub mod bloom;
+pub mod lines;
+
+use std::io::Cursor;
+pub use orbitbody::OrbitBody;
+
+/// Frames are drawn in linear high dynamic range, so light beyond the displayable range
+/// can bloom before the frame is copied to the window
+pub type ColorFormat = gfx::format::Rgba16F;
+pub type WindowFormat = gfx::format::Srgba8;
+pub type DepthFormat = gfx::format::Depth;
+
+
+gfx_defines! {
+    constant ShaderTime {
+        ms_ticks: f32 = "ticks",
+    }
+
+    constant UserViewTransform {
+        view: [[f32; 4]; 4] = "view",
+        proj: [[f32; 4]; 4] = "proj",
+    }
+}
+
+/// Loads png data as a texture, returning an error description on failure
+pub fn load_texture<R, F>(factory: &mut F,
+                          data: &[u8])
+                          -> Result<gfx::handle::ShaderResourceView<R, [f32; 4]>, String>
+                          where R: gfx::Resources,
+                                F: gfx::Factory<R> {
+    use gfx::texture as tex;
+    let img = image::load(Cursor::new(data), image::PNG)
+        .map_err(|err| format!("Invalid png: {}", err))?
+        .to_rgba();
+    let (width, height) = img.dimensions();
+    let kind = tex::Kind::D2(width as tex::Size, height as tex::Size, tex::AaMode::Single);
+    factory.create_texture_immutable_u8::<gfx::format::Srgba8>(kind, &[&img])
+        .map(|(_, view)| view)
+        .map_err(|err| format!("Texture creation failed: {:?}", err))
+}
@@ -0,0 +1,2 @@
+//! Flat coloured lines & triangles, shared by the grid, arrows & overlays
+pub mod render;
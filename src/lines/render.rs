@@ -0,0 +1,125 @@
+use super::super::{UserViewTransform, ColorFormat, DepthFormat};
+use cgmath::*;
+use gfx::*;
+use gfx::traits::FactoryExt;
+use gfx;
+use gfx_shader_watch::*;
+
+#[derive(VertexData, Debug, Clone, Copy, PartialEq)]
+pub struct LineVertex {
+    pub position: [f32; 2],
+    /// linear rgb & opacity
+    pub color: [f32; 4],
+}
+
+gfx_defines! {
+    constant LineLocals {
+        depth: f32 = "depth",
+    }
+
+    pipeline linepipe {
+        vbuf: VertexBuffer<LineVertex> = (),
+        out: BlendTarget<ColorFormat> = ("out_color", state::ColorMask::all(), preset::blend::ALPHA),
+        out_depth: DepthTarget<DepthFormat> = preset::depth::LESS_EQUAL_WRITE,
+        global_transform: ConstantBuffer<UserViewTransform> = "global_transform",
+        locals: ConstantBuffer<LineLocals> = "line_locals",
+    }
+}
+
+/// Transform for vertices already in normalised device coordinates
+pub fn screen_transform() -> UserViewTransform {
+    UserViewTransform {
+        view: Matrix4::identity().into(),
+        proj: Matrix4::identity().into(),
+    }
+}
+
+/// Draws line lists & triangle lists of flat coloured vertices, from a vertex buffer kept
+/// between draws & only reallocated when it needs to grow
+pub struct LineBrush<R: Resources, F: Factory<R>> {
+    line_pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = linepipe),
+    fill_pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = linepipe),
+    slice: Slice<R>,
+    data: linepipe::Data<R>,
+    depth: f32,
+}
+
+impl<R: Resources, F: Factory<R> + Clone> LineBrush<R, F> {
+    /// :depth z of the vertices before transforming, ie 0.9 to be behind bodies & curves
+    /// or -1 in front of everything with the screen transform
+    pub fn new(mut factory: F,
+               target: &handle::RenderTargetView<R, ColorFormat>,
+               depth_target: &handle::DepthStencilView<R, DepthFormat>,
+               depth: f32)
+               -> LineBrush<R, F>
+    {
+        let data = linepipe::Data {
+            vbuf: create_vertex_buffer(&mut factory, 0),
+            out: target.clone(),
+            out_depth: depth_target.clone(),
+            global_transform: factory.create_constant_buffer(1),
+            locals: factory.create_constant_buffer(1),
+        };
+        let slice = Slice::new_match_vertex_buffer(&data.vbuf);
+
+        let line_pso_cell = debug_watcher_pso_cell!(
+            pipe = linepipe,
+            vertex_shader = "shader/vert.glsl",
+            fragment_shader = "shader/frag.glsl",
+            factory = factory.clone(),
+            primitive = Primitive::LineList).expect("Line pso");
+        let fill_pso_cell = debug_watcher_pso_cell!(
+            pipe = linepipe,
+            vertex_shader = "shader/vert.glsl",
+            fragment_shader = "shader/frag.glsl",
+            factory = factory).expect("Line fill pso");
+
+        LineBrush { line_pso_cell, fill_pso_cell, slice, data, depth }
+    }
+
+    /// Draws a line list, each pair of vertices a line
+    pub fn draw_lines<C: CommandBuffer<R>>(&mut self,
+                                           encoder: &mut Encoder<R, C>,
+                                           transform: &UserViewTransform,
+                                           vertices: &[LineVertex]) {
+        if self.upload(encoder, transform, vertices) {
+            encoder.draw(&self.slice, self.line_pso_cell.pso(), &self.data);
+        }
+    }
+
+    /// Draws a triangle list, each 3 vertices a filled triangle
+    pub fn draw_triangles<C: CommandBuffer<R>>(&mut self,
+                                               encoder: &mut Encoder<R, C>,
+                                               transform: &UserViewTransform,
+                                               vertices: &[LineVertex]) {
+        if self.upload(encoder, transform, vertices) {
+            encoder.draw(&self.slice, self.fill_pso_cell.pso(), &self.data);
+        }
+    }
+
+    /// Updates the buffers for drawing vertices, returns false if there's nothing to draw
+    fn upload<C: CommandBuffer<R>>(&mut self,
+                                   encoder: &mut Encoder<R, C>,
+                                   transform: &UserViewTransform,
+                                   vertices: &[LineVertex]) -> bool {
+        if vertices.is_empty() {
+            return false;
+        }
+        if self.data.vbuf.len() < vertices.len() {
+            let capacity = vertices.len().next_power_of_two();
+            self.data.vbuf = create_vertex_buffer(self.line_pso_cell.factory(), capacity);
+        }
+        encoder.update_buffer(&self.data.vbuf, vertices, 0).expect("Line vertices");
+        encoder.update_constant_buffer(&self.data.global_transform, transform);
+        encoder.update_constant_buffer(&self.data.locals, &LineLocals { depth: self.depth });
+        self.slice.end = vertices.len() as u32;
+        true
+    }
+}
+
+fn create_vertex_buffer<R: Resources, F: Factory<R>>(factory: &mut F, capacity: usize)
+    -> handle::Buffer<R, LineVertex>
+{
+    factory.create_buffer(capacity, buffer::Role::Vertex, memory::Usage::Dynamic, Bind::empty())
+        .expect("Line vertex buffer")
+}
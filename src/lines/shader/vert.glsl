@@ -0,0 +1,22 @@
+#version 330 core
+
+layout(std140) uniform;
+
+uniform global_transform {
+    mat4 view;
+    mat4 proj;
+};
+
+uniform line_locals {
+    float depth;
+};
+
+in vec2 position;
+in vec4 color;
+
+out vec4 line_color;
+
+void main() {
+    line_color = color;
+    gl_Position = proj * view * vec4(position, depth, 1.0);
+}
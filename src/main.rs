@@ -1,81 +1,185 @@
-#![cfg_attr(feature = "bench", feature(test))]
-#[cfg(feature = "bench")]
-extern crate test;
-
 #[macro_use] extern crate log;
-#[macro_use] extern crate gfx;
-#[macro_use] extern crate gfx_macros;
-#[macro_use] extern crate gfx_shader_watch;
+extern crate prototype_orbit;
 extern crate pretty_env_logger;
+extern crate gfx;
 extern crate gfx_window_glutin;
 extern crate glutin;
 extern crate time;
-extern crate image;
-extern crate cgmath;
-extern crate gfx_text;
-extern crate easer;
-extern crate num;
-extern crate uuid;
-extern crate rayon;
-extern crate single_value_channel;
-
-mod input;
-mod state;
-mod background;
-mod orbitbody;
-mod ease;
-mod compute;
-mod debug;
-mod orbitcurve;
-mod seer;
-
-use gfx::{Device};
+
+use gfx::{Device, Factory};
 use glutin::*;
-use std::io::Cursor;
+use std::env;
+use std::io::{self, Write};
+use std::process;
+use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
-use state::*;
-use orbitbody::OrbitBody;
+use prototype_orbit::*;
+use prototype_orbit::state::*;
 
 const DESIRED_FPS: u32 = 256;
 const DESIRED_DETLA: f64 = 1.0 / DESIRED_FPS as f64;
 
-pub type ColorFormat = gfx::format::Srgba8;
-pub type DepthFormat = gfx::format::Depth;
+const CLEAR_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 0.0];
 
+const USAGE: &str = "Usage: prototype_orbit [--scenario FILE | --benchmark BODIES] [--camera-path FILE [--capture DIR]] \
+                     [--star-seed SEED] [--no-nebula] [--no-bloom] [--arrows linear|log[:FACTOR]] \
+                     [--split CAMERAS]";
 
-gfx_defines! {
-    constant ShaderTime {
-        ms_ticks: f32 = "ticks",
-    }
+struct Args {
+    bodies: Option<Vec<OrbitBody>>,
+    /// start paused without curves, to measure rendering
+    benchmark: bool,
+    camera_path: Option<camerapath::CameraPath>,
+    capture: Option<String>,
+    background: background::Background,
+    /// show body arrows from the start with this scale
+    arrows: Option<arrows::ArrowScale>,
+    /// cameras to split the window between
+    split: usize,
+    bloom: bool,
+}
 
-    constant UserViewTransform {
-        view: [[f32; 4]; 4] = "view",
-        proj: [[f32; 4]; 4] = "proj",
+fn parse_args() -> Result<Args, String> {
+    let mut args = env::args().skip(1);
+    let mut parsed = Args {
+        bodies: None,
+        benchmark: false,
+        camera_path: None,
+        capture: None,
+        background: background::Background::default(),
+        arrows: None,
+        split: 1,
+        bloom: true,
+    };
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("Missing value for {}", name));
+        match arg.as_str() {
+            "--scenario" => {
+                let file = value("--scenario")?;
+                parsed.bodies = Some(scenario::load(&file)
+                    .map_err(|err| format!("Invalid scenario {}: {}", file, err))?);
+            }
+            "--benchmark" => {
+                let count = value("--benchmark")?;
+                let count = count.parse().map_err(|_| format!("Invalid body count `{}`", count))?;
+                parsed.bodies = Some(scenario::benchmark(count));
+                parsed.benchmark = true;
+            }
+            "--camera-path" => {
+                let file = value("--camera-path")?;
+                parsed.camera_path = Some(camerapath::CameraPath::load(&file)
+                    .map_err(|err| format!("Invalid camera path {}: {}", file, err))?);
+            }
+            "--capture" => parsed.capture = Some(value("--capture")?),
+            "--star-seed" => {
+                let seed = value("--star-seed")?;
+                parsed.background.seed = seed.parse().map_err(|_| format!("Invalid star seed `{}`", seed))?;
+            }
+            "--no-nebula" => parsed.background.nebula = 0.0,
+            "--arrows" => parsed.arrows = Some(arrows::ArrowScale::parse(&value("--arrows")?)?),
+            "--split" => {
+                let count = value("--split")?;
+                parsed.split = match count.parse() {
+                    Ok(count) if count > 0 => count,
+                    _ => return Err(format!("Invalid camera count `{}`", count)),
+                };
+            }
+            "--no-bloom" => parsed.bloom = false,
+            "--help" | "-h" => return Err(USAGE.into()),
+            _ => return Err(format!("Unexpected argument `{}`\n{}", arg, USAGE)),
+        }
     }
+    if parsed.capture.is_some() && parsed.camera_path.is_none() {
+        return Err("--capture requires a --camera-path".into());
+    }
+    Ok(parsed)
 }
 
-const CLEAR_COLOR: [f32; 4] = [0.05, 0.05, 0.05, 0.0];
+/// Brushes drawing the world as seen by the active camera
+struct WorldBrushes<R: gfx::Resources, F: gfx::Factory<R>> {
+    target: gfx::handle::RenderTargetView<R, ColorFormat>,
+    background: background::render::BackgroundBrush<R, F>,
+    field: field::render::FieldBrush<R, F>,
+    grid: grid::render::GridBrush<R, F>,
+    orbit_curves: orbitcurve::render::OrbitCurveBrush<R, F>,
+    orbit_bodies: orbitbody::render::OrbitBodyBrush<R, F>,
+    arrows: arrows::render::ArrowBrush<R, F>,
+    labels: labels::render::LabelBrush<R, F>,
+}
 
-pub fn load_texture<R, F>(factory: &mut F,
-                          data: &[u8])
-                          -> gfx::handle::ShaderResourceView<R, [f32; 4]>
-                          where R: gfx::Resources,
-                                F: gfx::Factory<R> {
-    use gfx::texture as tex;
-    let img = image::load(Cursor::new(data), image::PNG)
-        .expect("!image::load")
-        .to_rgba();
-    let (width, height) = img.dimensions();
-    let kind = tex::Kind::D2(width as tex::Size, height as tex::Size, tex::AaMode::Single);
-    factory.create_texture_immutable_u8::<ColorFormat>(kind, &[&img])
-        .expect("!create_texture_immutable_u8")
-        .1
+impl<R: gfx::Resources, F: gfx::Factory<R> + Clone> WorldBrushes<R, F> {
+    fn new(factory: &F,
+           target: &gfx::handle::RenderTargetView<R, ColorFormat>,
+           depth_target: &gfx::handle::DepthStencilView<R, DepthFormat>,
+           background: &background::Background) -> WorldBrushes<R, F> {
+        WorldBrushes {
+            target: target.clone(),
+            background: background::render::BackgroundBrush::new(
+                factory.clone(), target, depth_target, background.clone()),
+            field: field::render::FieldBrush::new(factory.clone(), target, depth_target),
+            grid: grid::render::GridBrush::new(factory.clone(), target, depth_target),
+            orbit_curves: orbitcurve::render::OrbitCurveBrush::new(factory.clone(), target, depth_target),
+            orbit_bodies: orbitbody::render::OrbitBodyBrush::new(factory.clone(), target, depth_target),
+            arrows: arrows::render::ArrowBrush::new(factory.clone(), target, depth_target),
+            labels: labels::render::LabelBrush::new(factory),
+        }
+    }
+
+    fn draw<C: gfx::CommandBuffer<R>>(&mut self,
+                                      encoder: &mut gfx::Encoder<R, C>,
+                                      state: &State,
+                                      camera: &camera::Camera,
+                                      cursor: Option<(i32, i32)>,
+                                      trails: &orbitcurve::Trails) {
+        let transform = UserViewTransform {
+            view: camera.view().into(),
+            proj: camera.projection().into(),
+        };
+
+        self.background.draw(encoder, camera);
+        if state.field_mode != field::FieldMode::Off {
+            self.field.draw(encoder, state, camera);
+        }
+        if state.render_grid {
+            self.grid.draw(encoder, &transform, camera);
+        }
+
+        let trails = if state.render_trails { trails.in_world(camera.frame.as_ref()) } else { Vec::new() };
+        // curves are plotted in a single frame, other cameras would show them misplaced
+        let curves_visible = state.render_curves &&
+            camera.frame.map(|f| f.bodies) == state.drawables.curves_corotating;
+        let curves = state.drawables.orbit_curves.iter().filter(|_| curves_visible);
+        self.orbit_curves.draw(encoder, &transform, trails.iter().chain(curves), camera.visible_world_range());
+
+        self.orbit_bodies.draw(encoder, &transform, &state.drawables.orbit_bodies);
+        if state.render_arrows {
+            self.arrows.draw(encoder, &transform, state, camera);
+        }
+
+        if state.render_labels {
+            self.labels.draw(encoder, &self.target, state, camera).unwrap();
+        }
+        if state.render_grid {
+            self.grid.draw_text(encoder, &self.target, camera, cursor).unwrap();
+        }
+    }
+}
+
+/// A camera's world brushes, drawing offscreen when the window is split
+struct CameraView<R: gfx::Resources, F: gfx::Factory<R>> {
+    target: Option<camera::render::ViewportTarget<R>>,
+    brushes: WorldBrushes<R, F>,
 }
 
 pub fn main() {
     pretty_env_logger::init().unwrap();
 
+    let args = parse_args().unwrap_or_else(|err| {
+        let _ = writeln!(io::stderr(), "{}", err);
+        process::exit(2);
+    });
+
     let (win_width, win_height) = (1024, 768); // blog size: 800, 478
     let events_loop = EventsLoop::new();
     let builder = WindowBuilder::new()
@@ -85,30 +189,80 @@ pub fn main() {
         .with_gl(GlRequest::Specific(Api::OpenGl, (3, 3)))
         .with_multisampling(0);
 
-    let (window, mut device, mut factory, main_color, main_depth) =
-            gfx_window_glutin::init::<ColorFormat, DepthFormat>(builder, &events_loop);
+    let (window, mut device, mut factory, window_color, _) =
+            gfx_window_glutin::init::<WindowFormat, DepthFormat>(builder, &events_loop);
 
     window.set_position(2560 / 2 + 100, 100); // for development purposes
 
     let (width_px, height_px) = window.get_inner_size_pixels().unwrap();
+    // frames are drawn offscreen in high dynamic range, then bloomed onto the window
+    let (_, frame, main_color) = factory.create_render_target::<ColorFormat>(width_px as u16, height_px as u16)
+        .expect("Frame target");
+    let main_depth = factory.create_depth_stencil_view_only::<DepthFormat>(width_px as u16, height_px as u16)
+        .expect("Frame depth target");
 
     // Compute logic in seperate thread(s)
-    let mut state_get = compute::start(State::new(width_px, height_px), events_loop);
+    let bindings = bindings::InputMap::load_or_default(bindings::BINDINGS_FILE);
+    let (mut capture, capture_acks) = match args.capture {
+        Some(dir) => {
+            let frames = capture::FrameCapture::new(&dir, width_px, height_px)
+                .unwrap_or_else(|err| panic!("Cannot capture to {}: {}", dir, err));
+            info!("Capturing frames to {}", dir);
+            let (acks, acks_recv) = mpsc::channel();
+            (Some((frames, acks)), Some(acks_recv))
+        }
+        None => (None, None),
+    };
+    let mut last_captured = None;
+    let mut initial_state = State::new(width_px, height_px);
+    initial_state.split_cameras(args.split);
+    initial_state.render_bloom = args.bloom;
+    if let Some(bodies) = args.bodies {
+        initial_state.drawables.orbit_bodies = bodies;
+    }
+    if let Some(scale) = args.arrows {
+        initial_state.render_arrows = true;
+        initial_state.arrow_scale = scale;
+    }
+    if args.benchmark {
+        // simulating is O(n^2), so only measure rendering
+        initial_state.pause = true;
+        initial_state.render_curves = false;
+        initial_state.render_trails = false;
+    }
+    if args.benchmark || capture.is_some() {
+        // measure & capture just the world, overlays can be toggled back on
+        initial_state.render_minimap = false;
+        initial_state.render_labels = false;
+    }
+    let mut state_get = compute::start(initial_state,
+                                       events_loop,
+                                       bindings.clone(),
+                                       args.camera_path,
+                                       capture_acks,
+                                       args.benchmark);
     let start = time::precise_time_s();
 
     // Render logic in main thread
     let mut encoder: gfx::Encoder<_, _> = factory.create_command_buffer().into();
-    let mut orbit_body_brush = orbitbody::render::OrbitBodyBrush::new(
-        factory.clone(), &main_color, &main_depth);
-    let mut background_brush = background::render::BackgroundBrush::new(
-        factory.clone(), &main_color, &main_depth);
-    let mut debug_info_brush = debug::render::DebugInfoBrush::new(&factory);
-    let mut orbit_curve_brushes = Vec::new();
+    let background = args.background;
+    let mut views: Vec<CameraView<_, _>> = Vec::new();
+    // past paths of the bodies seen by each camera, in its frame
+    let mut trails: Vec<orbitcurve::Trails> = Vec::new();
+    let mut view_viewports = Vec::new();
+    let mut viewport_brush = camera::render::ViewportBrush::new(factory.clone(), &main_color);
+    let mut minimap_brush = minimap::render::MinimapBrush::new(factory.clone(), &main_color, &main_depth);
+    let mut debug_info_brush = debug::render::DebugInfoBrush::new(factory.clone(), &main_color, &main_depth);
+    let mut bindings_brush = bindings::render::BindingsBrush::new(&factory, &bindings);
+    let mut bloom_brush = bloom::render::BloomBrush::new(factory.clone(), &frame, &window_color);
 
     let (mut delta_sum, mut delta_count) = (0.0, 0);
     let mut passed = time::precise_time_s() - start;
 
     let mut mean_fps = DESIRED_FPS; // optimistic
+    let mut frame_times = debug::TimeGraph::new();
+    let mut step_times = debug::TimeGraph::new();
+    let mut last_total_step_time = 0.0;
     loop {
         let last_passed = passed;
         passed = time::precise_time_s() - start;
@@ -120,34 +274,56 @@ pub fn main() {
             break;
         }
 
-        let projection = state.projection();
-        let view = state.view;
-        let visible_world_range = state.visible_world_range();
+        let viewports: Vec<_> = state.cameras.iter().map(|camera| camera.viewport).collect();
+        if viewports != view_viewports {
+            views = if viewports.len() == 1 {
+                // a single camera draws straight onto the window
+                vec!(CameraView {
+                    target: None,
+                    brushes: WorldBrushes::new(&factory, &main_color, &main_depth, &background),
+                })
+            } else {
+                viewports.iter().map(|viewport| {
+                    let target = camera::render::ViewportTarget::new(
+                        &mut factory, *viewport, (state.screen_width, state.screen_height));
+                    let brushes = WorldBrushes::new(&factory, &target.color, &target.depth, &background);
+                    CameraView { target: Some(target), brushes }
+                }).collect()
+            };
+            view_viewports = viewports;
+        }
+
+        trails.resize(state.cameras.len(), orbitcurve::Trails::default());
+        for (trails, camera) in trails.iter_mut().zip(&state.cameras) {
+            if state.render_trails {
+                trails.record(&state.drawables.orbit_bodies, camera.frame.as_ref());
+            } else {
+                // hidden trails restart when shown, rather than jumping across the gap
+                *trails = orbitcurve::Trails::default();
+            }
+        }
 
         encoder.clear(&main_color, CLEAR_COLOR);
         encoder.clear_depth(&main_depth, 1.0);
 
-        let transform = UserViewTransform {
-            view: view.into(),
-            proj: projection.into(),
-        };
-
-        background_brush.draw(&mut encoder, &transform);
-
-        if state.render_curves {
-            while state.drawables.orbit_curves.len() > orbit_curve_brushes.len() {
-                // add a curve brush per necessary curve, keep them forever
-                orbit_curve_brushes.push(orbitcurve::render::OrbitCurveBrush::new(
-                        factory.clone(), &main_color, &main_depth));
-            }
-            for (idx, curve) in state.drawables.orbit_curves.iter().enumerate() {
-                orbit_curve_brushes[idx].draw(&mut encoder, &transform, curve, visible_world_range);
+        for (idx, view) in views.iter_mut().enumerate() {
+            let camera = &state.cameras[idx];
+            let cursor = if state.camera_at(state.cursor) == Some(idx) { Some(state.cursor) } else { None };
+            match view.target {
+                Some(ref target) => {
+                    encoder.clear(&target.color, CLEAR_COLOR);
+                    encoder.clear_depth(&target.depth, 1.0);
+                    view.brushes.draw(&mut encoder, state, camera, cursor, &trails[idx]);
+                    viewport_brush.draw(&mut encoder, target);
+                }
+                None => view.brushes.draw(&mut encoder, state, camera, cursor, &trails[idx]),
             }
         }
 
-        orbit_body_brush.draw(&mut encoder, &transform, &state.drawables.orbit_bodies);
-
-
+        frame_times.push(delta);
+        // all compute steps since the last frame
+        step_times.push(state.debug_info.total_step_time - last_total_step_time);
+        last_total_step_time = state.debug_info.total_step_time;
         delta_sum += delta;
         delta_count += 1;
         if delta_sum >= 1.0 { // ie update around every second
@@ -156,9 +332,31 @@ pub fn main() {
             delta_count = 0;
         }
 
-        debug_info_brush.draw(&mut encoder, &main_color, &state.debug_info.add_render_info(mean_fps))
-            .unwrap();
+        if state.render_minimap {
+            minimap_brush.draw(&mut encoder, state);
+        }
+        let debug_info = debug::DebugInfo {
+            compute: &state.debug_info,
+            mean_fps,
+            following: state.followed_body().map(|body| body.display_name()),
+            frame_times: &frame_times,
+            step_times: &step_times,
+            curve_vertices: views.iter().map(|view| view.brushes.orbit_curves.vertex_count()).sum(),
+            expanded: state.show_debug,
+        };
+        debug_info_brush.draw(&mut encoder, &debug_info).unwrap();
+        if state.show_bindings {
+            bindings_brush.draw(&mut encoder, &main_color, state.screen_width).unwrap();
+        }
+        bloom_brush.draw(&mut encoder, state.render_bloom);
         encoder.flush(&mut device);
+        if let (Some(frame), Some(&mut (ref mut frames, ref acks))) = (state.capture_frame, capture.as_mut()) {
+            if last_captured != Some(frame) {
+                frames.save(&mut device, frame).expect("!capture frame");
+                last_captured = Some(frame);
+                let _ = acks.send(frame);
+            }
+        }
         window.swap_buffers().unwrap();
         device.cleanup();
 
@@ -0,0 +1,124 @@
+//! Corner overview inset of the whole system, clicking it recentres the active camera
+pub mod render;
+
+use cgmath::*;
+use state::State;
+use std::f32;
+
+/// Inset side length in pixels
+pub const INSET_PIXELS: i32 = 180;
+/// Gap between the inset & the screen's right edge
+const MARGIN_PIXELS: i32 = 10;
+/// Gap between the inset & the screen's bottom edge, leaving room for the cursor readout
+const BOTTOM_MARGIN_PIXELS: i32 = 50;
+/// Extra space around the system's bounds
+const PADDING: f32 = 1.1;
+
+/// Screen pixel (left, top) of the inset, it is `INSET_PIXELS` square
+pub fn inset_position(state: &State) -> (i32, i32) {
+    (state.screen_width as i32 - MARGIN_PIXELS - INSET_PIXELS,
+     state.screen_height as i32 - BOTTOM_MARGIN_PIXELS - INSET_PIXELS)
+}
+
+/// Whether a window pixel is on the inset
+pub fn in_inset(state: &State, (x, y): (i32, i32)) -> bool {
+    let (left, top) = inset_position(state);
+    x >= left && y >= top && x <= left + INSET_PIXELS && y <= top + INSET_PIXELS
+}
+
+/// Square world area shown by the minimap, north up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Overview {
+    pub center: Vector2<f32>,
+    /// half the side length
+    pub half_size: f32,
+}
+
+impl Overview {
+    /// Fits all bodies & curves, or the active camera's view if there are none
+    pub fn of(state: &State) -> Overview {
+        let drawables = &state.drawables;
+        let body_bounds = drawables.orbit_bodies.iter().flat_map(|body| {
+            let radius = Vector2::new(body.radius, body.radius);
+            vec!(body.center - radius, body.center + radius)
+        });
+        let curve_plots = drawables.orbit_curves.iter().flat_map(|curve| curve.plots.iter().cloned());
+        let mut points = body_bounds.chain(curve_plots).map(|p| p.cast::<f32>());
+
+        let (mut min, mut max) = match points.next() {
+            Some(point) => (point, point),
+            None => state.camera().visible_world_range(),
+        };
+        for point in points {
+            min = Vector2::new(min.x.min(point.x), min.y.min(point.y));
+            max = Vector2::new(max.x.max(point.x), max.y.max(point.y));
+        }
+        let half_size = (max.x - min.x).max(max.y - min.y) / 2.0 * PADDING;
+        Overview { center: (min + max) / 2.0, half_size: half_size.max(f32::EPSILON) }
+    }
+
+    /// Screen pixel position of a world location inside the inset
+    pub fn world_to_inset(&self, state: &State, world: Vector2<f32>) -> Vector2<f32> {
+        let (left, top) = inset_position(state);
+        let normalised = (world - self.center) / self.half_size; // -1 to 1
+        Vector2::new(left as f32 + (normalised.x + 1.0) / 2.0 * INSET_PIXELS as f32,
+                     top as f32 + (1.0 - normalised.y) / 2.0 * INSET_PIXELS as f32)
+    }
+
+    /// World location of a screen pixel if it's inside the inset
+    pub fn inset_to_world(&self, state: &State, pixel: (i32, i32)) -> Option<Vector2<f32>> {
+        if !in_inset(state, pixel) {
+            return None;
+        }
+        let (left, top) = inset_position(state);
+        let (x, y) = (pixel.0 - left, pixel.1 - top);
+        let normalised = Vector2::new(x as f32 * 2.0 / INSET_PIXELS as f32 - 1.0,
+                                      1.0 - y as f32 * 2.0 / INSET_PIXELS as f32);
+        Some(self.center + normalised * self.half_size)
+    }
+
+    /// Clamps a world location into the overview
+    pub fn clamp(&self, world: Vector2<f32>) -> Vector2<f32> {
+        let (min, max) = (self.center.x - self.half_size, self.center.x + self.half_size);
+        let x = world.x.max(min).min(max);
+        let (min, max) = (self.center.y - self.half_size, self.center.y + self.half_size);
+        Vector2::new(x, world.y.max(min).min(max))
+    }
+}
+
+#[cfg(test)]
+mod minimap_test {
+    use super::*;
+    use orbitbody::OrbitBody;
+
+    fn state_with_bodies(centers: &[(f64, f64)]) -> State {
+        let mut state = State::new(800, 600);
+        state.drawables.orbit_bodies = centers.iter().map(|&(x, y)| OrbitBody::new((x, y).into(), (0.0, 0.0).into(), 1.0, 1.0)).collect();
+        state.drawables.orbit_curves.clear();
+        state
+    }
+
+    #[test]
+    fn overview_fits_bodies() {
+        let state = state_with_bodies(&[(-10.0, 0.0), (30.0, 4.0)]);
+        let overview = Overview::of(&state);
+        assert_eq!(overview.center, Vector2::new(10.0, 2.0));
+        assert!((overview.half_size - 21.0 * PADDING).abs() < 0.0001, "{:?}", overview);
+    }
+
+    #[test]
+    fn inset_round_trip() {
+        let state = state_with_bodies(&[(-10.0, -10.0), (10.0, 10.0)]);
+        let overview = Overview::of(&state);
+        let (left, top) = inset_position(&state);
+        assert_eq!((left, top), (800 - 10 - INSET_PIXELS, 600 - 50 - INSET_PIXELS));
+
+        let center = (left + INSET_PIXELS / 2, top + INSET_PIXELS / 2);
+        assert_eq!(overview.inset_to_world(&state, center), Some(Vector2::new(0.0, 0.0)));
+        let top_left = overview.inset_to_world(&state, (left, top)).unwrap();
+        assert_eq!(overview.world_to_inset(&state, top_left), Vector2::new(left as f32, top as f32));
+
+        assert_eq!(overview.inset_to_world(&state, (left - 1, top)), None);
+        assert_eq!(overview.inset_to_world(&state, (5, 5)), None);
+    }
+}
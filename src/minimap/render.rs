@@ -0,0 +1,141 @@
+use super::*;
+use super::super::{ColorFormat, DepthFormat};
+use gfx::*;
+use lines::render::{LineBrush, LineVertex, screen_transform};
+use orbitcurve::OrbitCurve;
+
+const BACKDROP_COLOR: [f32; 4] = [0.0, 0.02, 0.05, 0.8];
+const BORDER_COLOR: [f32; 4] = [0.3, 0.6, 0.8, 0.6];
+const VIEW_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 0.8];
+/// Curves are drawn with at most this many segments
+const MAX_CURVE_SEGMENTS: usize = 64;
+/// Smallest body dot half size, so distant bodies stay visible
+const MIN_BODY_PIXELS: f32 = 1.5;
+/// in front of everything
+const MINIMAP_DEPTH: f32 = -1.0;
+
+/// Maps screen pixels to normalised device coordinates
+struct Screen(f32, f32);
+
+impl Screen {
+    fn vertex(&self, pixel: Vector2<f32>, color: [f32; 4]) -> LineVertex {
+        LineVertex { position: [pixel.x / self.0 * 2.0 - 1.0, 1.0 - pixel.y / self.1 * 2.0], color }
+    }
+
+    /// Two triangles covering a pixel rectangle
+    fn push_rect(&self, verts: &mut Vec<LineVertex>, min: Vector2<f32>, max: Vector2<f32>, color: [f32; 4]) {
+        let corners = [min, Vector2::new(max.x, min.y), Vector2::new(min.x, max.y), max];
+        verts.extend([0, 1, 2, 1, 3, 2].iter().map(|&idx| self.vertex(corners[idx], color)));
+    }
+
+    /// Line list outline of a pixel rectangle
+    fn push_outline(&self, verts: &mut Vec<LineVertex>, min: Vector2<f32>, max: Vector2<f32>, color: [f32; 4]) {
+        let corners = [min, Vector2::new(max.x, min.y), max, Vector2::new(min.x, max.y)];
+        for idx in 0..4 {
+            verts.push(self.vertex(corners[idx], color));
+            verts.push(self.vertex(corners[(idx + 1) % 4], color));
+        }
+    }
+}
+
+/// Plots drawn for a curve, evenly skipping plots to keep under `MAX_CURVE_SEGMENTS` segments
+fn simplified_plots(curve: &OrbitCurve) -> Vec<Vector2<f32>> {
+    let len = curve.plots.len();
+    if len < 2 {
+        return Vec::new();
+    }
+    let step = ((len - 1) as f32 / MAX_CURVE_SEGMENTS as f32).ceil() as usize;
+    let mut indices: Vec<_> = (0..len).filter(|idx| idx % step == 0).collect();
+    if indices.last() != Some(&(len - 1)) {
+        indices.push(len - 1);
+    }
+    indices.iter().map(|&idx| curve.plots[idx].cast()).collect()
+}
+
+/// (backdrop triangles, curve & outline lines, body triangles) of the minimap
+fn minimap_vertices(state: &State, overview: &Overview)
+    -> (Vec<LineVertex>, Vec<LineVertex>, Vec<LineVertex>)
+{
+    let screen = Screen(state.screen_width as f32, state.screen_height as f32);
+    let (left, top) = inset_position(state);
+    let inset_min = Vector2::new(left as f32, top as f32);
+    let inset_max = inset_min + Vector2::new(INSET_PIXELS as f32, INSET_PIXELS as f32);
+    let to_inset = |world: Vector2<f32>| overview.world_to_inset(state, world);
+
+    let mut backdrop = Vec::new();
+    screen.push_rect(&mut backdrop, inset_min, inset_max, BACKDROP_COLOR);
+
+    let mut lines = Vec::new();
+    for curve in state.drawables.orbit_curves.iter().filter(|c| c.opacity > 0.0) {
+        let color = [curve.color[0], curve.color[1], curve.color[2], curve.opacity];
+        for pair in simplified_plots(curve).windows(2) {
+            lines.push(screen.vertex(to_inset(pair[0]), color));
+            lines.push(screen.vertex(to_inset(pair[1]), color));
+        }
+    }
+    let (view_min, view_max) = state.camera().visible_world_range();
+    // y flips from world to screen
+    let (view_top_left, view_bottom_right) = (Vector2::new(view_min.x, view_max.y), Vector2::new(view_max.x, view_min.y));
+    screen.push_outline(&mut lines,
+                        to_inset(overview.clamp(view_top_left)),
+                        to_inset(overview.clamp(view_bottom_right)),
+                        VIEW_COLOR);
+    screen.push_outline(&mut lines, inset_min, inset_max, BORDER_COLOR);
+
+    let pixels_per_world = INSET_PIXELS as f32 / (2.0 * overview.half_size);
+    let mut bodies = Vec::new();
+    for body in &state.drawables.orbit_bodies {
+        let center = to_inset(body.center.cast());
+        let half = Vector2::from_value((body.radius as f32 * pixels_per_world).max(MIN_BODY_PIXELS));
+        let color = [body.color[0], body.color[1], body.color[2], 1.0];
+        screen.push_rect(&mut bodies, center - half, center + half, color);
+    }
+    (backdrop, lines, bodies)
+}
+
+pub struct MinimapBrush<R: Resources, F: Factory<R>> {
+    lines: LineBrush<R, F>,
+}
+
+impl<R: Resources, F: Factory<R> + Clone> MinimapBrush<R, F> {
+    pub fn new(factory: F,
+               target: &handle::RenderTargetView<R, ColorFormat>,
+               depth_target: &handle::DepthStencilView<R, DepthFormat>)
+               -> MinimapBrush<R, F>
+    {
+        MinimapBrush { lines: LineBrush::new(factory, target, depth_target, MINIMAP_DEPTH) }
+    }
+
+    /// Draws the inset over the scene, call after drawing the world
+    pub fn draw<C: CommandBuffer<R>>(&mut self, encoder: &mut Encoder<R, C>, state: &State) {
+        let overview = Overview::of(state);
+        let (backdrop, lines, bodies) = minimap_vertices(state, &overview);
+        let transform = screen_transform();
+        self.lines.draw_triangles(encoder, &transform, &backdrop);
+        self.lines.draw_lines(encoder, &transform, &lines);
+        self.lines.draw_triangles(encoder, &transform, &bodies);
+    }
+}
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+
+    fn curve(plots: usize) -> OrbitCurve {
+        let mut curve = OrbitCurve::new();
+        curve.plots = (0..plots).map(|x| Vector2::new(x as f64, 0.0)).collect();
+        curve
+    }
+
+    #[test]
+    fn curves_simplified() {
+        assert_eq!(simplified_plots(&curve(1)).len(), 0);
+        assert_eq!(simplified_plots(&curve(10)).len(), 10);
+
+        let plots = simplified_plots(&curve(MAX_CURVE_SEGMENTS * 3 + 2));
+        assert!(plots.len() <= MAX_CURVE_SEGMENTS + 1, "{}", plots.len());
+        assert_eq!(plots[0], Vector2::new(0.0, 0.0));
+        // always ends at the last plot
+        assert_eq!(plots[plots.len() - 1], Vector2::new((MAX_CURVE_SEGMENTS * 3 + 1) as f32, 0.0));
+    }
+}
@@ -3,18 +3,130 @@ pub mod render;
 use super::*;
 use cgmath::*;
 use uuid::Uuid;
+use std::f64;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct OrbitBody {
     pub id: Uuid,
+    /// human readable name, shown in labels & logs. Shared so state clones don't copy it
+    pub name: Option<Arc<str>>,
     pub center: Vector2<f64>,
     pub radius: f64,
     pub mass: f64,
     pub velocity: Vector2<f64>,
+    /// linear rgb colour, also used for the body's curve
+    pub color: [f32; 3],
+    /// intensity of the halo drawn around the body, 0 for none
+    pub glow: f32,
+    /// png file drawn on the body, rotated by #spin. Shared so state clones don't copy it
+    pub texture: Option<Arc<str>>,
+    /// anti-clockwise rotation of the body's texture in radians
+    pub spin: f64,
+    /// radians per second the spin advances
+    pub spin_rate: f64,
+}
+
+pub const WHITE: [f32; 3] = [1.0, 1.0, 1.0];
+
+/// Bodies at least this heavy shine as stars
+pub const STAR_MASS: f64 = 500.0;
+/// Brightest a star's mass makes it
+const MAX_STAR_LUMINOSITY: f32 = 2.0;
+
+/// Distinct colours for bodies without a specified colour
+const PALETTE: [[f32; 3]; 6] = [
+    [1.0, 0.85, 0.45],
+    [0.45, 0.7, 1.0],
+    [1.0, 0.45, 0.4],
+    [0.5, 0.95, 0.55],
+    [0.85, 0.55, 1.0],
+    [0.4, 0.95, 0.95]];
+
+/// Returns a palette colour for the body at an index
+pub fn palette_color(idx: usize) -> [f32; 3] {
+    PALETTE[idx % PALETTE.len()]
 }
 
 impl OrbitBody {
+    /// A plain white body, customise the rest with `OrbitBody { .., ..OrbitBody::new(..) }`
+    pub fn new(center: Vector2<f64>, velocity: Vector2<f64>, mass: f64, radius: f64) -> OrbitBody {
+        OrbitBody {
+            id: Uuid::new_v4(),
+            name: None,
+            center,
+            radius,
+            mass,
+            velocity,
+            color: WHITE,
+            glow: 0.0,
+            texture: None,
+            spin: 0.0,
+            spin_rate: 0.0,
+        }
+    }
+
     pub fn update(&mut self, delta: f64) {
         self.center += self.velocity * delta;
+        self.spin = (self.spin + self.spin_rate * delta) % (2.0 * f64::consts::PI);
+    }
+
+    /// Brightness of the body's bloom, 0 for none. Stars shine more with each order of
+    /// magnitude of mass
+    pub fn luminosity(&self) -> f32 {
+        if self.mass < STAR_MASS {
+            return 0.0;
+        }
+        (0.5 + 0.5 * (self.mass / STAR_MASS).log10() as f32).min(MAX_STAR_LUMINOSITY)
+    }
+
+    /// The body's name, or its id if unnamed
+    pub fn display_name(&self) -> String {
+        match self.name {
+            Some(ref name) => name.to_string(),
+            None => self.id.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod orbitbody_test {
+    use super::*;
+
+    #[test]
+    fn update_advances_spin() {
+        let mut body = OrbitBody {
+            spin_rate: 2.0,
+            ..OrbitBody::new((0.0, 0.0).into(), (1.0, 0.0).into(), 1.0, 1.0)
+        };
+        body.update(0.5);
+        assert_eq!(body.center.x, 0.5);
+        assert_eq!(body.spin, 1.0);
+
+        body.update(4.0);
+        assert!(body.spin >= 0.0 && body.spin < 2.0 * f64::consts::PI, "{}", body.spin);
+        assert!((body.spin - (9.0 - 2.0 * f64::consts::PI)).abs() < 1e-9);
+
+        assert_eq!(body.display_name(), body.id.to_string());
+        body.name = Some("Io".into());
+        assert_eq!(body.display_name(), "Io");
+    }
+
+    #[test]
+    fn luminosity_from_star_mass() {
+        let mut body = OrbitBody::new((0.0, 0.0).into(), (0.0, 0.0).into(), 1.0, 1.0);
+        assert_eq!(body.luminosity(), 0.0);
+
+        body.mass = STAR_MASS;
+        assert_eq!(body.luminosity(), 0.5);
+        body.mass = STAR_MASS * 10.0;
+        assert_eq!(body.luminosity(), 1.0);
+        body.mass = STAR_MASS * 1e6;
+        assert_eq!(body.luminosity(), MAX_STAR_LUMINOSITY);
+
+        // the halo doesn't make a body shine
+        body.mass = 1.0;
+        body.glow = 0.3;
+        assert_eq!(body.luminosity(), 0.0);
     }
 }
@@ -1,39 +1,70 @@
-use super::{UserViewTransform, ColorFormat, DepthFormat, OrbitBody};
-use cgmath::Matrix4;
+use super::{UserViewTransform, ColorFormat, DepthFormat, OrbitBody, load_texture};
 use gfx::*;
 use gfx::traits::FactoryExt;
 use gfx;
 use gfx_shader_watch::*;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
 
 #[derive(VertexData, Debug, Clone, Copy)]
 pub struct OrbitBodyVertex {
     position: [f32; 2],
-    local_idx: u32,
 }
 
-#[derive(ConstantBuffer, Debug, Clone, Copy)]
-pub struct OrbitBodyTransform {
-    transform: [[f32; 4]; 4],
+/// Per-body instance data
+#[derive(VertexData, Debug, Clone, Copy, PartialEq)]
+pub struct OrbitBodyInstance {
+    center: [f32; 2],
+    radius: f32,
+    color: [f32; 3],
+    glow: f32,
+    /// surface brightness beyond white, blooming when it passes the displayable range
+    luminosity: f32,
+    spin: f32,
+    /// 1 if the texture should be drawn, otherwise 0
+    textured: f32,
 }
 
 gfx_defines! {
     pipeline orbitbodypipe {
         vbuf: VertexBuffer<OrbitBodyVertex> = (),
+        instances: InstanceBuffer<OrbitBodyInstance> = (),
         out: BlendTarget<ColorFormat> = ("out_color", state::ColorMask::all(), preset::blend::ALPHA),
         out_depth: gfx::DepthTarget<DepthFormat> = preset::depth::LESS_EQUAL_WRITE,
         global_transform: ConstantBuffer<UserViewTransform> = "global_transform",
-        local_transform: ConstantBuffer<OrbitBodyTransform> = "local_transform",
+        texture: TextureSampler<[f32; 4]> = "t_texture",
     }
 }
 
-impl OrbitBodyTransform {
-    fn new(transform: [[f32; 4]; 4]) -> OrbitBodyTransform {
-        OrbitBodyTransform {
-            transform,
+impl OrbitBodyInstance {
+    fn new(body: &OrbitBody) -> OrbitBodyInstance {
+        OrbitBodyInstance {
+            center: [body.center.x as f32, body.center.y as f32],
+            radius: body.radius as f32,
+            color: body.color,
+            glow: body.glow,
+            luminosity: body.luminosity(),
+            spin: body.spin as f32,
+            textured: if body.texture.is_some() { 1.0 } else { 0.0 },
         }
     }
 }
 
+/// Instances grouped by texture, in order of first use, so each group can be drawn in one call
+fn instance_groups(bodies: &[OrbitBody]) -> Vec<(Option<&str>, Vec<OrbitBodyInstance>)> {
+    let mut groups: Vec<(Option<&str>, Vec<OrbitBodyInstance>)> = Vec::new();
+    for body in bodies {
+        let file = body.texture.as_ref().map(AsRef::as_ref);
+        let instance = OrbitBodyInstance::new(body);
+        match groups.iter_mut().find(|group| group.0 == file) {
+            Some(group) => group.1.push(instance),
+            None => groups.push((file, vec!(instance))),
+        }
+    }
+    groups
+}
+
 // equilateral triangle with incircle radius 1, and incircle center (0, 0)
 // ref: https://rechneronline.de/pi/equilateral-triangle.php
 //    C
@@ -60,14 +91,17 @@ const ROOT3: f64 = 1.7320508075688774;
 const BX: f64 = (6.0 / (2.0 * ROOT3));
 
 const ORBIT_BODY_VERTICES: [OrbitBodyVertex; 3] = [
-    OrbitBodyVertex{ position: [0.0, 2.0], local_idx: 0 },
-    OrbitBodyVertex{ position: [-BX as f32, -1.0], local_idx: 0 },
-    OrbitBodyVertex{ position: [BX as f32, -1.0], local_idx: 0 }];
+    OrbitBodyVertex{ position: [0.0, 2.0] },
+    OrbitBodyVertex{ position: [-BX as f32, -1.0] },
+    OrbitBodyVertex{ position: [BX as f32, -1.0] }];
 
 pub struct OrbitBodyBrush<R: Resources, F: Factory<R>> {
     pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = orbitbodypipe),
     slice: Slice<R>,
     data: orbitbodypipe::Data<R>,
+    /// textures by file, bodies without a (valid) texture use a blank texture
+    textures: HashMap<String, handle::ShaderResourceView<R, [f32; 4]>>,
+    blank_texture: handle::ShaderResourceView<R, [f32; 4]>,
 }
 
 impl<R: Resources, F: Factory<R>> OrbitBodyBrush<R, F> {
@@ -76,13 +110,17 @@ impl<R: Resources, F: Factory<R>> OrbitBodyBrush<R, F> {
                depth_target: &handle::DepthStencilView<R, DepthFormat>)
                -> OrbitBodyBrush<R, F>
     {
-        let (vertex_buffer, slice) = factory.create_vertex_buffer_with_slice(&[], ());
+        let (vertex_buffer, slice) = factory.create_vertex_buffer_with_slice(&ORBIT_BODY_VERTICES, ());
+        let kind = texture::Kind::D2(1, 1, texture::AaMode::Single);
+        let (_, blank_texture) = factory.create_texture_immutable_u8::<format::Srgba8>(kind, &[&[255; 4]])
+            .expect("OrbitBody blank texture");
         let data = orbitbodypipe::Data {
             vbuf: vertex_buffer,
+            instances: create_instance_buffer(&mut factory, 0),
             out: target.clone(),
             out_depth: depth_target.clone(),
             global_transform: factory.create_constant_buffer(1),
-            local_transform: factory.create_constant_buffer(0),
+            texture: (blank_texture.clone(), factory.create_sampler_linear()),
         };
 
         let pso_cell = debug_watcher_pso_cell!(
@@ -91,7 +129,31 @@ impl<R: Resources, F: Factory<R>> OrbitBodyBrush<R, F> {
             fragment_shader = "shader/frag.glsl",
             factory = factory).expect("OrbitBody pso");
 
-        OrbitBodyBrush { pso_cell, slice, data }
+        OrbitBodyBrush { pso_cell, slice, data, textures: HashMap::new(), blank_texture }
+    }
+
+    /// Returns the texture loaded from a png file, loading it on first use
+    fn texture(&mut self, file: Option<&str>) -> handle::ShaderResourceView<R, [f32; 4]> {
+        let file = match file {
+            Some(file) => file,
+            None => return self.blank_texture.clone(),
+        };
+        if !self.textures.contains_key(file) {
+            let mut data = Vec::new();
+            let loaded = File::open(file)
+                .and_then(|mut f| f.read_to_end(&mut data))
+                .map_err(|err| err.to_string())
+                .and_then(|_| load_texture(self.pso_cell.factory(), &data));
+            let texture = match loaded {
+                Ok(texture) => texture,
+                Err(err) => {
+                    warn!("Could not load texture {}: {}", file, err);
+                    self.blank_texture.clone()
+                }
+            };
+            self.textures.insert(file.to_owned(), texture);
+        }
+        self.textures[file].clone()
     }
 
     pub fn draw<C>(&mut self,
@@ -100,32 +162,66 @@ impl<R: Resources, F: Factory<R>> OrbitBodyBrush<R, F> {
                       bodies: &[OrbitBody]) where C: CommandBuffer<R> {
         encoder.update_constant_buffer(&self.data.global_transform, transform);
 
-        if self.data.vbuf.len() != bodies.len() * 3 {
-            self.data.local_transform = self.pso_cell.factory().create_constant_buffer(bodies.len());
-
-            let mut all_verts = Vec::with_capacity(bodies.len() * 3);
-            for body_idx in 0..bodies.len() {
-                for vert in &mut ORBIT_BODY_VERTICES {
-                    vert.local_idx = body_idx as u32;
-                    all_verts.push(*vert);
-                }
+        for (file, instances) in instance_groups(bodies) {
+            if self.data.instances.len() < instances.len() {
+                let capacity = instances.len().next_power_of_two();
+                self.data.instances = create_instance_buffer(self.pso_cell.factory(), capacity);
             }
-            let (vertex_buffer, slice) = self.pso_cell.factory().create_vertex_buffer_with_slice(all_verts.as_slice(), ());
-            self.data.vbuf = vertex_buffer;
-            self.slice = slice;
+            // each group is written to the start of the buffer, avoiding base instances unsupported by GL 3.3
+            encoder.update_buffer(&self.data.instances, &instances, 0).expect("OrbitBody instances");
+            self.data.texture.0 = self.texture(file);
+            let slice = Slice { instances: Some((instances.len() as u32, 0)), ..self.slice.clone() };
+            encoder.draw(&slice, self.pso_cell.pso(), &self.data);
         }
+    }
+}
 
-        for (idx, body) in bodies.iter().enumerate() {
-            let locals = OrbitBodyTransform::new(local_transform(body));
-            encoder.update_buffer(&self.data.local_transform, &[locals], idx).expect("OrbitBody draw");
+fn create_instance_buffer<R: Resources, F: Factory<R>>(factory: &mut F, capacity: usize)
+    -> handle::Buffer<R, OrbitBodyInstance>
+{
+    factory.create_buffer(capacity, buffer::Role::Vertex, memory::Usage::Dynamic, Bind::empty())
+        .expect("OrbitBody instance buffer")
+}
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+    use orbitbody::{palette_color, STAR_MASS};
+
+    fn body(x: f64, texture: Option<&str>) -> OrbitBody {
+        OrbitBody {
+            color: palette_color(0),
+            texture: texture.map(|t| t.into()),
+            spin: 1.5,
+            ..OrbitBody::new((x, 2.0).into(), (0.0, 0.0).into(), 1.0, 0.5)
         }
+    }
 
-        encoder.draw(&self.slice, self.pso_cell.pso(), &self.data);
+    #[test]
+    fn instances_grouped_by_texture() {
+        let bodies = vec!(body(1.0, None), body(2.0, Some("a.png")), body(3.0, None), body(4.0, Some("a.png")));
+        let groups = instance_groups(&bodies);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, None);
+        assert_eq!(groups[0].1.iter().map(|i| i.center[0]).collect::<Vec<_>>(), vec!(1.0, 3.0));
+        assert_eq!(groups[1].0, Some("a.png"));
+        assert_eq!(groups[1].1.iter().map(|i| i.center[0]).collect::<Vec<_>>(), vec!(2.0, 4.0));
     }
-}
 
-fn local_transform(body: &OrbitBody) -> [[f32; 4]; 4] {
-    let scale = Matrix4::from_nonuniform_scale(body.radius as f32, body.radius as f32, 1.0);
-    let translate = Matrix4::from_translation([body.center.x as f32, body.center.y as f32, 0.0].into());
-    (translate * scale).into()
+    #[test]
+    fn stars_shine_beyond_white() {
+        let star = OrbitBody::new((0.0, 0.0).into(), (0.0, 0.0).into(), STAR_MASS * 10.0, 1.0);
+        assert_eq!(OrbitBodyInstance::new(&star).luminosity, 1.0);
+        assert_eq!(OrbitBodyInstance::new(&body(1.0, None)).luminosity, 0.0);
+    }
+
+    #[test]
+    fn instance_from_body() {
+        let instance = OrbitBodyInstance::new(&body(1.0, Some("a.png")));
+        assert_eq!(instance.center, [1.0, 2.0]);
+        assert_eq!(instance.radius, 0.5);
+        assert_eq!(instance.spin, 1.5);
+        assert_eq!(instance.textured, 1.0);
+    }
 }
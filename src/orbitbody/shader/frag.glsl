@@ -1,23 +1,56 @@
 #version 330 core
 
 in vec2 model;
-in float alpha_base;
+flat in vec3 body_color;
+flat in float body_glow;
+flat in float body_luminosity;
+flat in float body_spin;
+flat in float body_textured;
+
+uniform sampler2D t_texture;
 
 out vec4 out_color;
 const float border_dist = 0.81;
+const float glow_extent = 3.0;
+
+/// Body surface colour, the texture rotated by the spin if textured
+vec4 texture_surface() {
+    if (body_textured < 0.5) {
+        return vec4(body_color, 1.0);
+    }
+    // rotate clockwise to sample, so the texture appears rotated anti-clockwise
+    float s = sin(-body_spin);
+    float c = cos(-body_spin);
+    vec2 local = vec2(c * model.x - s * model.y, s * model.x + c * model.y);
+    // image rows are top down
+    return texture(t_texture, vec2(0.5 + local.x * 0.5, 0.5 - local.y * 0.5));
+}
+
+/// Surface colour brightened by the luminosity, stars exceed the displayable range to bloom
+vec4 surface() {
+    vec4 color = texture_surface();
+    return vec4(color.rgb * (1.0 + body_luminosity), color.a);
+}
 
 void main() {
     float dist = pow(model.x, 2) + pow(model.y, 2);
     if (dist > 1.0) {
-        discard;
+        // halo fading out over the glow extent
+        float glow_dist = (sqrt(dist) - 1.0) / glow_extent;
+        if (body_glow <= 0.0 || glow_dist >= 1.0) {
+            discard;
+        }
+        out_color = vec4(body_color, 0.5 * min(body_glow, 1.0) * pow(1.0 - glow_dist, 3));
     }
-    vec3 color = vec3(1.0, 1.0, 1.0);
-    if (dist > border_dist) {
+    else if (dist > border_dist) {
         float fade_factor = (dist - border_dist) / (1.0 - border_dist);
         float alpha = mix(1.0, 0.0, fade_factor);
-        out_color = vec4(color, alpha);
+        vec4 color = surface();
+        // fade into the glow rather than the background
+        alpha = max(alpha * color.a, 0.5 * min(body_glow, 1.0));
+        out_color = vec4(color.rgb, alpha);
     }
     else {
-        out_color = vec4(color, 1.0);
+        out_color = surface();
     }
 }
@@ -2,26 +2,39 @@
 
 layout(std140) uniform;
 
-struct OrbitBodyTransform {
-    mat4 transform;
-};
-
 uniform global_transform {
     mat4 view;
     mat4 proj;
 };
 
-uniform local_transform {
-    OrbitBodyTransform locals[1];
-};
-
 in vec2 position;
-in uint local_idx;
+// per instance
+in vec2 center;
+in float radius;
+in vec3 color;
+in float glow;
+in float luminosity;
+in float spin;
+in float textured;
 
 out vec2 model;
+flat out vec3 body_color;
+flat out float body_glow;
+flat out float body_luminosity;
+flat out float body_spin;
+flat out float body_textured;
+
+// glow reaches this many radii beyond the body
+const float glow_extent = 3.0;
 
 void main() {
-    mat4 local = locals[local_idx].transform;
-    model = position;
-    gl_Position = proj * view * local * vec4(position, 0.5, 1.0);
+    // enlarge the triangle to cover any glow
+    vec2 scaled = glow > 0.0 ? position * (1.0 + glow_extent) : position;
+    model = scaled;
+    body_color = color;
+    body_glow = glow;
+    body_luminosity = luminosity;
+    body_spin = spin;
+    body_textured = textured;
+    gl_Position = proj * view * vec4(center + scaled * radius, 0.5, 1.0);
 }
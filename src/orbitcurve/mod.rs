@@ -1,16 +1,53 @@
 pub mod render;
 
 use cgmath::*;
+use orbitbody::{OrbitBody, WHITE};
+use camera::RotatingFrame;
+use uuid::Uuid;
+
+/// World distance between recorded trail plots
+const TRAIL_PLOT_DISTANCE: f64 = 0.1;
+const TRAIL_MAX_PLOTS: usize = 1000;
+
+/// Whether a curve is a predicted or past path, changing how it's drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveStyle {
+    /// fades out towards the future
+    Prediction,
+    /// thinner, fading out towards the past
+    Trail,
+}
 
 #[derive(Debug, Clone)]
 pub struct OrbitCurve {
     pub plots: Vec<Vector2<f64>>,
     pub opacity: f32,
+    /// linear rgb colour, usually the colour of the body the curve predicts
+    pub color: [f32; 3],
+    pub style: CurveStyle,
 }
 
 impl OrbitCurve {
     pub fn new() -> OrbitCurve {
-        OrbitCurve { plots: Vec::new(), opacity: 1.0 }
+        OrbitCurve { plots: Vec::new(), opacity: 1.0, color: WHITE, style: CurveStyle::Prediction }
+    }
+
+    pub fn trail(color: [f32; 3]) -> OrbitCurve {
+        OrbitCurve { color, style: CurveStyle::Trail, ..OrbitCurve::new() }
+    }
+
+    /// Adds a plot if at least `min_distance` from the latest, dropping the oldest plots
+    /// beyond `max_plots`
+    pub fn extend_trail(&mut self, plot: Vector2<f64>, min_distance: f64, max_plots: usize) {
+        let far_enough = match self.plots.last() {
+            Some(last) => last.distance2(plot) >= min_distance * min_distance,
+            None => true,
+        };
+        if far_enough {
+            self.plots.push(plot);
+            let excess = self.plots.len().saturating_sub(max_plots);
+            self.remove_oldest_plots(excess);
+        }
     }
 
     pub fn remove_oldest_plots(&mut self, n: usize) {
@@ -18,9 +55,7 @@ impl OrbitCurve {
             self.plots.clear();
         }
         else {
-            for _ in 0..n {
-                self.plots.remove(0);
-            }
+            self.plots.drain(..n);
         }
     }
 
@@ -85,13 +120,53 @@ impl OrbitCurve {
         }
 
         plots.shrink_to_fit();
-        OrbitCurve { plots, opacity: self.opacity }
+        OrbitCurve { plots, opacity: self.opacity, color: self.color, style: self.style }
+    }
+}
+
+/// Past path of each body, by body index. Plots are relative to the co-rotating frame of
+/// the camera they're recorded for, so trails move with the frame as predictions do.
+#[derive(Debug, Clone, Default)]
+pub struct Trails {
+    curves: Vec<OrbitCurve>,
+    /// bodies of the frame the plots are relative to, `None` for world plots
+    frame_bodies: Option<(Uuid, Uuid)>,
+}
+
+impl Trails {
+    /// Extends each body's trail with its current position, restarting the trails when
+    /// the frame changes
+    pub fn record(&mut self, bodies: &[OrbitBody], frame: Option<&RotatingFrame>) {
+        let frame_bodies = frame.map(|f| f.bodies);
+        if frame_bodies != self.frame_bodies {
+            self.curves.clear();
+            self.frame_bodies = frame_bodies;
+        }
+        self.curves.truncate(bodies.len());
+        while self.curves.len() < bodies.len() {
+            self.curves.push(OrbitCurve::trail(bodies[self.curves.len()].color));
+        }
+        for (trail, body) in self.curves.iter_mut().zip(bodies) {
+            let plot = frame.map(|f| f.to_local(body.center)).unwrap_or(body.center);
+            trail.extend_trail(plot, TRAIL_PLOT_DISTANCE, TRAIL_MAX_PLOTS);
+        }
+    }
+
+    /// Trails with world plots, placed by the current frame they were recorded in
+    pub fn in_world(&self, frame: Option<&RotatingFrame>) -> Vec<OrbitCurve> {
+        self.curves.iter()
+            .map(|curve| OrbitCurve {
+                plots: curve.plots.iter().map(|&p| frame.map(|f| f.to_world(p)).unwrap_or(p)).collect(),
+                ..OrbitCurve::trail(curve.color)
+            })
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod orbitcurve_compute {
     use super::*;
+    use std::f64;
 
     #[test]
     fn remove_oldest_plots() {
@@ -117,6 +192,56 @@ mod orbitcurve_compute {
         assert_eq!(curve.plots.len(), 0);
     }
 
+    #[test]
+    fn extend_trail() {
+        let mut trail = OrbitCurve::trail(WHITE);
+        trail.extend_trail((0.0, 0.0).into(), 1.0, 3);
+        trail.extend_trail((0.5, 0.0).into(), 1.0, 3);
+        assert_eq!(trail.plots.len(), 1);
+
+        for x in 1..5 {
+            trail.extend_trail((x as f64, 0.0).into(), 1.0, 3);
+        }
+        assert_eq!(trail.plots, vec!((2.0, 0.0).into(), (3.0, 0.0).into(), (4.0, 0.0).into()));
+        assert_eq!(trail.style, CurveStyle::Trail);
+    }
+
+    #[test]
+    fn record_trails() {
+        let mut bodies: Vec<_> = (0..4)
+            .map(|idx| OrbitBody::new((idx as f64, 0.0).into(), (0.0, 0.0).into(), 1.0, 0.1))
+            .collect();
+        bodies[1].color = [1.0, 0.0, 0.0];
+        let mut trails = Trails::default();
+        trails.record(&bodies, None);
+        assert_eq!(trails.curves.len(), 4);
+        assert_eq!(trails.curves[1].color, bodies[1].color);
+
+        bodies[0].center.x += TRAIL_PLOT_DISTANCE;
+        bodies.pop();
+        trails.record(&bodies, None);
+        assert_eq!(trails.curves.len(), 3);
+        assert_eq!(trails.curves[0].plots.len(), 2);
+        assert_eq!(trails.curves[1].plots.len(), 1);
+    }
+
+    #[test]
+    fn trails_follow_the_rotating_frame() {
+        let bodies = vec!(OrbitBody::new((2.0, 0.0).into(), (0.0, 0.0).into(), 1.0, 0.1));
+        let ids = (bodies[0].id, bodies[0].id);
+        let frame = |angle| RotatingFrame { bodies: ids, pivot: Vector2::zero(), angle };
+        let mut trails = Trails::default();
+        trails.record(&bodies, Some(&frame(0.0)));
+
+        // a quarter turn later the plot turns with the frame
+        let plot = trails.in_world(Some(&frame(f64::consts::FRAC_PI_2))).remove(0).plots[0];
+        assert!(plot.distance(Vector2::new(0.0, 2.0)) < 1e-12, "{:?}", plot);
+
+        // other frames restart the trails
+        trails.record(&bodies, None);
+        assert_eq!(trails.in_world(None)[0].plots, vec!(Vector2::new(2.0, 0.0)));
+    }
+
     #[test]
     fn with_minimum_plot_distance() {
         let mut curve = OrbitCurve::new();
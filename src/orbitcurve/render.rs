@@ -1,5 +1,5 @@
 use super::super::{UserViewTransform, ColorFormat, DepthFormat};
-use super::OrbitCurve;
+use super::{OrbitCurve, CurveStyle};
 use cgmath::*;
 use gfx::*;
 use gfx::traits::FactoryExt;
@@ -7,29 +7,22 @@ use gfx;
 use gfx_shader_watch::*;
 
 const LINE_WIDTH: f32 = 0.1;
+const TRAIL_LINE_WIDTH: f32 = 0.05;
+/// trails are drawn fainter than predictions
+const TRAIL_OPACITY: f32 = 0.6;
 
-#[derive(VertexData, Debug, Clone, Copy)]
+/// Vertex of a curve segment quad, carrying the segment it belongs to so all curves
+/// can be drawn together
+#[derive(VertexData, Debug, Clone, Copy, PartialEq)]
 pub struct OrbitCurveVertex {
     position: [f32; 2],
-    local_idx: u32,
-}
-
-impl OrbitCurveVertex {
-    fn new<V: Into<[f32; 2]>>(pos: V, index: usize) -> OrbitCurveVertex {
-        OrbitCurveVertex {
-            position: pos.into(),
-            local_idx: index as u32,
-        }
-    }
-}
-
-#[derive(ConstantBuffer, Debug, Clone, Copy)]
-pub struct OrbitCurveBezier {
+    /// start of the previous segment, equal to `p1` for a curve's first segment
+    p0: [f32; 2],
     p1: [f32; 2],
     p2: [f32; 2],
-    opacity: f32,
+    /// linear rgb & segment opacity
+    color: [f32; 4],
     thickness: f32,
-    std140_offset: [u32; 2],
 }
 
 gfx_defines! {
@@ -38,14 +31,16 @@ gfx_defines! {
         out: BlendTarget<ColorFormat> = ("out_color", state::ColorMask::all(), preset::blend::ALPHA),
         out_depth: gfx::DepthTarget<DepthFormat> = preset::depth::LESS_EQUAL_WRITE,
         global_transform: ConstantBuffer<UserViewTransform> = "global_transform",
-        beziers: ConstantBuffer<OrbitCurveBezier> = "beziers",
     }
 }
 
+/// Draws all curves with a single draw call, from a vertex buffer kept between frames
+/// & only reallocated when it needs to grow
 pub struct OrbitCurveBrush<R: Resources, F: Factory<R>> {
     pso_cell: debug_watcher_pso_cell_type!(R, F, pipe = orbitcurvepipe),
     slice: Slice<R>,
     data: orbitcurvepipe::Data<R>,
+    vertices: Vec<OrbitCurveVertex>,
 }
 
 struct WorldView {
@@ -71,111 +66,196 @@ fn perp(vec: Vector2<f32>) -> Vector2<f32> {
     Vector2::new(-vec.y, vec.x)
 }
 
+/// Opacity of a segment, predictions fade out towards their end & trails towards their start
+fn segment_opacity(curve: &OrbitCurve, segment: usize, segments: usize) -> f32 {
+    let opacity = match curve.style {
+        CurveStyle::Prediction => curve.opacity * (1.0 - (segment + 1) as f32 / segments as f32),
+        CurveStyle::Trail => curve.opacity * TRAIL_OPACITY * (segment + 1) as f32 / segments as f32,
+    };
+    if opacity < 0.00001 { // try to avoid f32 math irregularities
+        0.0
+    }
+    else {
+        opacity
+    }
+}
+
+/// Appends 2 triangles per visible curve segment, joined at the mid angle of adjacent segments
+fn push_curve_vertices(verts: &mut Vec<OrbitCurveVertex>, curve: &OrbitCurve, view: &WorldView) {
+    if curve.opacity < 0.00001 || !curve.is_drawable() {
+        return;
+    }
+    let plot = |idx: usize| -> Vector2<f32> { curve.plots[idx].cast() };
+    let segments = curve.plots.len() - 1;
+    let width = match curve.style {
+        CurveStyle::Prediction => LINE_WIDTH,
+        CurveStyle::Trail => TRAIL_LINE_WIDTH,
+    };
+
+    let perp_onwards = perp((plot(1) - plot(0)).normalize_to(width / 2.0));
+    let mut last_edge = (plot(0) - perp_onwards, plot(0) + perp_onwards);
+
+    for plot_idx in 0..segments {
+        // calculate vertices around c2
+        let c1 = plot(plot_idx);
+        let c2 = plot(plot_idx + 1);
+        let c3 = curve.plots.get(plot_idx + 2).map(|p| p.cast());
+
+        let c1_perp_onwards = perp((c2 - c1).normalize_to(width / 2.0));
+        // calculate vertices at points using previous plot and perpendicular line width
+        let mut p3 = c2 - c1_perp_onwards;
+        let mut p4 = c2 + c1_perp_onwards;
+
+        if let Some(c3) = c3 {
+            // calculate vertices at points using next plot and perpendicular line width
+            let c2_perp_onwards = perp((c3 - c2).normalize_to(width / 2.0));
+            // take average of previous & next to reach mid
+            p3 = (p3 + c2 - c2_perp_onwards) / 2.0;
+            p4 = (p4 + c2 + c2_perp_onwards) / 2.0;
+        }
+        let (p1, p2) = last_edge;
+        last_edge = (p3, p4);
+
+        if !view.contains(c1) && !view.contains(c2) &&
+            (plot_idx == 0 || !view.contains(plot(plot_idx - 1))) &&
+            (c3.is_none() || !view.contains(c3.unwrap())) {
+            // Current points, and neighbours are outsite the current view, so skip
+            continue;
+        }
+
+        let opacity = segment_opacity(curve, plot_idx, segments);
+        let c0 = if plot_idx == 0 { c1 } else { plot(plot_idx - 1) };
+        let vertex = |position: Vector2<f32>| OrbitCurveVertex {
+            position: position.into(),
+            p0: c0.into(),
+            p1: c1.into(),
+            p2: c2.into(),
+            color: [curve.color[0], curve.color[1], curve.color[2], opacity],
+            thickness: width,
+        };
+        verts.extend([p1, p2, p3, p2, p4, p3].iter().map(|p| vertex(*p)));
+    }
+}
+
 impl<R: Resources, F: Factory<R>> OrbitCurveBrush<R, F> {
     pub fn new(mut factory: F,
                target: &handle::RenderTargetView<R, ColorFormat>,
                depth_target: &handle::DepthStencilView<R, DepthFormat>,)
                -> OrbitCurveBrush<R, F>
     {
-        let (vertex_buffer, slice) = factory.create_vertex_buffer_with_slice(&[], ());
         let data = orbitcurvepipe::Data {
-            vbuf: vertex_buffer,
+            vbuf: create_vertex_buffer(&mut factory, 0),
             out: target.clone(),
             out_depth: depth_target.clone(),
             global_transform: factory.create_constant_buffer(1),
-            beziers: factory.create_constant_buffer(0),
         };
+        let slice = Slice::new_match_vertex_buffer(&data.vbuf);
 
         let pso_cell = debug_watcher_pso_cell!(
             pipe = orbitcurvepipe,
             vertex_shader = "shader/vert.glsl",
             fragment_shader = "shader/frag.glsl",
-            factory = factory,
-            primitive = Primitive::TriangleStrip).expect("OrbitCurve pso");
+            factory = factory).expect("OrbitCurve pso");
 
-        OrbitCurveBrush { pso_cell, slice, data }
+        OrbitCurveBrush { pso_cell, slice, data, vertices: Vec::new() }
     }
 
-    pub fn draw<C>(&mut self,
-                   encoder: &mut Encoder<R, C>,
-                   transform: &UserViewTransform,
-                   curve: &OrbitCurve,
-                   (visible_min, visible_max): (Vector2<f32>, Vector2<f32>))
-                   where C: CommandBuffer<R> {
-        if curve.opacity < 0.00001 || !curve.is_drawable() {
-            return;
-        }
-
+    pub fn draw<'a, C, I>(&mut self,
+                          encoder: &mut Encoder<R, C>,
+                          transform: &UserViewTransform,
+                          curves: I,
+                          (visible_min, visible_max): (Vector2<f32>, Vector2<f32>))
+                          where C: CommandBuffer<R>, I: IntoIterator<Item=&'a OrbitCurve> {
         let view = WorldView { min: visible_min, max: visible_max }.with_extra(LINE_WIDTH);
 
-        encoder.update_constant_buffer(&self.data.global_transform, transform);
-        self.data.beziers = self.pso_cell.factory().create_constant_buffer(curve.plots.len() - 1);
-
-        let mut all_verts = Vec::with_capacity((curve.plots.len() - 1) * 2 + 2);
-
-        let c_1st = curve.plots[0].cast();
-        let c_2nd = curve.plots[1].cast();
-        let perp_onwards = perp((c_2nd - c_1st).normalize_to(LINE_WIDTH / 2.0));
-
-        let v1 = OrbitCurveVertex::new(c_1st - perp_onwards, 0);
-        let v2 = OrbitCurveVertex::new(c_1st + perp_onwards, 0);
-        all_verts.push(v1);
-        all_verts.push(v2);
-
-        for plot_idx in 0..(curve.plots.len()-1) {
-            // calculate vertices around c2
-            let c1 = curve.plots[plot_idx].cast();
-            let c2 = curve.plots[plot_idx + 1].cast();
-            let c3 = curve.plots.get(plot_idx + 2).map(|p| p.cast());
-
-            if !view.contains(c1) && !view.contains(c2) &&
-                (plot_idx == 0 || !view.contains(curve.plots[plot_idx-1].cast())) &&
-                (c3.is_none() || !view.contains(c3.unwrap())) {
-                // Current points, and neighbours are outsite the current view, so skip
-                continue;
-            }
-
-            let c1_perp_onwards = perp((c2 - c1).normalize_to(LINE_WIDTH / 2.0));
-            // calculate vertices at points using previous plot and perpendicular line width
-            let mut p3 = c2 - c1_perp_onwards;
-            let mut p4 = c2 + c1_perp_onwards;
-
-            if let Some(c3) = c3 {
-                // calculate vertices at points using next plot and perpendicular line width
-                let c2_perp_onwards = perp((c3 - c2).normalize_to(LINE_WIDTH / 2.0));
-                let p3_2 = c2 - c2_perp_onwards;
-                let p4_2 = c2 + c2_perp_onwards;
-                // take average of previous & next to reach mid
-                p3 = (p3 + p3_2) / 2.0;
-                p4 = (p4 + p4_2) / 2.0;
-            }
-
-            let v3 = OrbitCurveVertex::new(p3, plot_idx + 1);
-            let v4 = OrbitCurveVertex::new(p4, plot_idx + 1);
-
-            all_verts.push(v3);
-            all_verts.push(v4);
-
-            let mut opacity = curve.opacity * (1.0 - (plot_idx+1) as f32 / (curve.plots.len()-1) as f32);
-            if opacity < 0.00001 { // try to avoid f32 math irregularities
-                opacity = 0.0;
-            }
-
-            let bezier = OrbitCurveBezier {
-                p1: c1.into(),
-                p2: c2.into(),
-                opacity,
-                thickness: LINE_WIDTH,
-                std140_offset: [0; 2],
-            };
-            encoder.update_buffer(&self.data.beziers, &[bezier], plot_idx).unwrap();
+        self.vertices.clear();
+        for curve in curves {
+            push_curve_vertices(&mut self.vertices, curve, &view);
+        }
+        if self.vertices.is_empty() {
+            return;
         }
 
-        let (vertex_buffer, slice) =
-            self.pso_cell.factory().create_vertex_buffer_with_slice(all_verts.as_slice(), ());
-        self.data.vbuf = vertex_buffer;
-        self.slice = slice;
+        if self.data.vbuf.len() < self.vertices.len() {
+            let capacity = self.vertices.len().next_power_of_two();
+            self.data.vbuf = create_vertex_buffer(self.pso_cell.factory(), capacity);
+        }
+        encoder.update_buffer(&self.data.vbuf, &self.vertices, 0).expect("OrbitCurve vertices");
+        encoder.update_constant_buffer(&self.data.global_transform, transform);
 
+        self.slice.end = self.vertices.len() as u32;
         encoder.draw(&self.slice, self.pso_cell.pso(), &self.data);
     }
+
+    /// Vertices drawn by the last `draw`
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len()
+    }
+}
+
+fn create_vertex_buffer<R: Resources, F: Factory<R>>(factory: &mut F, capacity: usize)
+    -> handle::Buffer<R, OrbitCurveVertex>
+{
+    factory.create_buffer(capacity, buffer::Role::Vertex, memory::Usage::Dynamic, Bind::empty())
+        .expect("OrbitCurve vertex buffer")
+}
+
+#[cfg(test)]
+mod render_test {
+    use super::*;
+
+    fn curve(plots: usize, opacity: f32) -> OrbitCurve {
+        let mut curve = OrbitCurve::new();
+        curve.opacity = opacity;
+        curve.color = [1.0, 0.5, 0.0];
+        curve.plots = (0..plots).map(|x| Vector2::new(x as f64, 0.0)).collect();
+        curve
+    }
+
+    fn view(min: f32, max: f32) -> WorldView {
+        WorldView { min: Vector2::new(min, min), max: Vector2::new(max, max) }
+    }
+
+    #[test]
+    fn segment_vertices() {
+        let mut verts = Vec::new();
+        push_curve_vertices(&mut verts, &curve(5, 1.0), &view(-100.0, 100.0));
+
+        assert_eq!(verts.len(), 4 * 6);
+        // opacity fades along the curve, colour is per curve
+        assert_eq!(verts[0].color, [1.0, 0.5, 0.0, 0.75]);
+        assert_eq!(verts[6 * 3].color[3], 0.0);
+        // first segment has no previous segment
+        assert_eq!(verts[0].p0, verts[0].p1);
+        assert_eq!(verts[6].p0, [0.0, 0.0]);
+        assert_eq!((verts[6].p1, verts[6].p2), ([1.0, 0.0], [2.0, 0.0]));
+        // straight curve quads are line width wide
+        assert_eq!(verts[6].position, [1.0, -LINE_WIDTH / 2.0]);
+        assert_eq!(verts[6 + 4].position, [2.0, LINE_WIDTH / 2.0]);
+    }
+
+    #[test]
+    fn trails_fade_towards_the_past() {
+        let mut trail = OrbitCurve::trail([1.0, 1.0, 1.0]);
+        trail.plots = curve(5, 1.0).plots;
+        let mut verts = Vec::new();
+        push_curve_vertices(&mut verts, &trail, &view(-100.0, 100.0));
+
+        assert_eq!(verts.len(), 4 * 6);
+        assert_eq!(verts[0].color[3], TRAIL_OPACITY * 0.25);
+        assert_eq!(verts[6 * 3].color[3], TRAIL_OPACITY);
+        assert_eq!(verts[0].thickness, TRAIL_LINE_WIDTH);
+    }
+
+    #[test]
+    fn skips_invisible() {
+        let mut verts = Vec::new();
+        push_curve_vertices(&mut verts, &curve(5, 0.0), &view(-100.0, 100.0));
+        push_curve_vertices(&mut verts, &curve(3, 1.0), &view(-100.0, 100.0));
+        assert!(verts.is_empty());
+
+        // only segments near the view
+        push_curve_vertices(&mut verts, &curve(10, 1.0), &view(-1.0, 0.5));
+        assert_eq!(verts.len(), 2 * 6);
+    }
 }
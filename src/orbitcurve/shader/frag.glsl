@@ -1,70 +1,29 @@
 #version 330 core
 
-layout(std140) uniform;
-
-struct OrbitCurveBezier {
-    vec2 p1;
-    vec2 p2;
-    float opacity;
-    float thickness;
-};
-
-uniform beziers {
-    OrbitCurveBezier u_beziers[1];
-};
-
 in vec2 model_pos;
-flat in uint bezier_idx;
+flat in vec2 seg_p0;
+flat in vec2 seg_p1;
+flat in vec2 seg_p2;
+flat in vec4 seg_color;
+flat in float seg_thickness;
 
 out vec4 out_color;
 
-vec2 linear_bezier(float t, vec2 p1, vec2 p2) {
-    return (1 - t) * p1 + t * p2;
-}
-
-/// :t float in [0,1]
-float distance_from_curve_at(float t) {
-    OrbitCurveBezier bezier = u_beziers[bezier_idx];
-    vec2 pt = linear_bezier(t, bezier.p1, bezier.p2);
-    float dist = distance(pt, model_pos);
-    if (bezier_idx != uint(0)) {
-        // consider previous section too
-        OrbitCurveBezier last_bezier = u_beziers[bezier_idx - uint(1)];
-        vec2 pt2 = linear_bezier(1 - t, last_bezier.p1, last_bezier.p2);
-        float dist2 = distance(pt2, model_pos);
-        dist = min(dist, dist2);
-    }
-
-    return dist;
-}
-
-float distance_from_curve() {
-    float from = 0.0;
-    float end = 1.0;
-    float dist_from = distance_from_curve_at(from);
-    float dist_end = distance_from_curve_at(end);
-
-    while(abs(from - end) > 0.00001) {
-        if (dist_end < dist_from) {
-            from = (from + from + end) / 3.0;
-        }
-        else {
-            end = (from + end + end) / 3.0;
-        }
-        dist_from = distance_from_curve_at(from);
-        dist_end = distance_from_curve_at(end);
-    }
-
-    return min(dist_end, dist_from);
+/// distance from the line segment a->b
+float distance_from_segment(vec2 a, vec2 b) {
+    vec2 ab = b - a;
+    float len2 = dot(ab, ab);
+    float t = len2 > 0.0 ? clamp(dot(model_pos - a, ab) / len2, 0.0, 1.0) : 0.0;
+    return distance(a + t * ab, model_pos);
 }
 
 void main() {
-    OrbitCurveBezier bezier = u_beziers[bezier_idx];
-
-    float distance_from_curve = distance_from_curve();
-    float max_dist = bezier.thickness / 2;
+    // consider previous section too
+    float distance_from_curve = min(distance_from_segment(seg_p1, seg_p2),
+                                    distance_from_segment(seg_p0, seg_p1));
+    float max_dist = seg_thickness / 2;
     if (distance_from_curve <= max_dist) {
-        out_color = vec4(1.0, 1.0, 1.0, bezier.opacity);
+        out_color = seg_color;
         // blend anti-alias
         out_color.a *= mix(1.0, 0.0, distance_from_curve / max_dist);
     }
@@ -8,13 +8,25 @@ uniform global_transform {
 };
 
 in vec2 position;
-in uint local_idx;
+in vec2 p0;
+in vec2 p1;
+in vec2 p2;
+in vec4 color;
+in float thickness;
 
 out vec2 model_pos;
-flat out uint bezier_idx;
+flat out vec2 seg_p0;
+flat out vec2 seg_p1;
+flat out vec2 seg_p2;
+flat out vec4 seg_color;
+flat out float seg_thickness;
 
 void main() {
-    bezier_idx = local_idx;
     model_pos = position;
+    seg_p0 = p0;
+    seg_p1 = p1;
+    seg_p2 = p2;
+    seg_color = color;
+    seg_thickness = thickness;
     gl_Position = proj * view * vec4(position, 0.75, 1.0);
 }
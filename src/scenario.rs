@@ -0,0 +1,255 @@
+//! Simple text scenario files describing a list of orbit bodies
+//!
+//! One body per line as whitespace separated values `x y vx vy mass radius`,
+//! optionally followed by `color=` sRGB hex `rrggbb` or `r,g,b` in [0, 1], `glow=` intensity,
+//! `texture=` png file, relative to the scenario file, `spin=` radians per second & `name=`,
+//! with `_` shown as a space.
+//! Blank lines & anything after a `#` are ignored
+//! ```text
+//! # x     y    vx    vy     mass    radius
+//!   0.0   0.0  0.0  -1.0    1660.0  1.2     color=ffd973 glow=0.8
+//!   3.5   0.0  0.0   1.6    1000.0  0.9     color=0.45,0.7,1 name=Binary_B
+//! ```
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use OrbitBody;
+use compute::GRAVITY;
+use orbitbody::palette_color;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScenarioError {
+    /// 1-based line number the error occurred on, 0 if not line specific
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.reason)
+        }
+        else {
+            write!(f, "line {}: {}", self.line, self.reason)
+        }
+    }
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<OrbitBody>, ScenarioError> {
+    let mut contents = String::new();
+    File::open(path.as_ref())
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|err| ScenarioError {
+            line: 0,
+            reason: format!("Could not read {}: {}", path.as_ref().display(), err),
+        })?;
+    let mut bodies = parse(&contents)?;
+    let dir = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
+    for texture in bodies.iter_mut().filter_map(|b| b.texture.as_mut()) {
+        *texture = dir.join(&**texture).to_string_lossy().into_owned().into();
+    }
+    Ok(bodies)
+}
+
+pub fn parse(scenario: &str) -> Result<Vec<OrbitBody>, ScenarioError> {
+    let mut bodies = Vec::new();
+    for (idx, line) in scenario.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let body_idx = bodies.len();
+        bodies.push(parse_body(line, body_idx).map_err(|reason| ScenarioError { line: idx + 1, reason })?);
+    }
+    if bodies.is_empty() {
+        return Err(ScenarioError { line: 0, reason: "No bodies defined".into() });
+    }
+    Ok(bodies)
+}
+
+fn parse_body(line: &str, idx: usize) -> Result<OrbitBody, String> {
+    let (extras, values): (Vec<&str>, Vec<&str>) = line.split_whitespace().partition(|v| v.contains('='));
+    let values = values.into_iter()
+        .map(|val| match val.parse::<f64>() {
+            Ok(v) if v.is_finite() => Ok(v),
+            _ => Err(format!("Invalid number `{}`", val)),
+        })
+        .collect::<Result<Vec<f64>, String>>()?;
+
+    if values.len() != 6 {
+        return Err(format!("Expected 6 values `x y vx vy mass radius`, got {}", values.len()));
+    }
+    if values[4] <= 0.0 || values[5] <= 0.0 {
+        return Err("mass & radius must be positive".into());
+    }
+
+    let mut body = OrbitBody {
+        color: palette_color(idx),
+        ..OrbitBody::new((values[0], values[1]).into(), (values[2], values[3]).into(), values[4], values[5])
+    };
+    for extra in extras {
+        let mut kv = extra.splitn(2, '=');
+        match (kv.next().unwrap_or(""), kv.next().unwrap_or("")) {
+            ("color", val) => body.color = parse_color(val)?,
+            ("glow", val) => body.glow = match val.parse::<f32>() {
+                Ok(glow) if glow.is_finite() && glow >= 0.0 => glow,
+                _ => return Err(format!("Invalid glow `{}`", val)),
+            },
+            ("texture", "") => return Err("Missing texture file".into()),
+            ("texture", val) => body.texture = Some(val.into()),
+            ("spin", val) => body.spin_rate = match val.parse::<f64>() {
+                Ok(spin) if spin.is_finite() => spin,
+                _ => return Err(format!("Invalid spin `{}`", val)),
+            },
+            ("name", "") => return Err("Missing name".into()),
+            ("name", val) => body.name = Some(val.replace('_', " ").into()),
+            (key, _) => return Err(format!("Unknown key `{}`", key)),
+        }
+    }
+    Ok(body)
+}
+
+/// Benchmark scene of a star circled by a disc of `count - 1` light bodies, laid out in a
+/// golden angle spiral so any count is deterministic & evenly spread
+pub fn benchmark(count: usize) -> Vec<OrbitBody> {
+    const STAR_MASS: f64 = 10_000.0;
+    let golden_angle = ::std::f64::consts::PI * (3.0 - 5f64.sqrt());
+    let star = OrbitBody {
+        color: palette_color(0),
+        glow: 0.8,
+        ..OrbitBody::new((0.0, 0.0).into(), (0.0, 0.0).into(), STAR_MASS, 2.0)
+    };
+    let mut bodies = Vec::with_capacity(count);
+    bodies.extend((1..count).map(|idx| {
+        let angle = idx as f64 * golden_angle;
+        let distance = 5.0 + (idx as f64).sqrt() * 0.2;
+        let speed = (GRAVITY * STAR_MASS / distance).sqrt();
+        let center = (distance * angle.cos(), distance * angle.sin()).into();
+        let velocity = (-speed * angle.sin(), speed * angle.cos()).into();
+        OrbitBody { color: palette_color(idx), ..OrbitBody::new(center, velocity, 0.001, 0.05) }
+    }));
+    if count > 0 {
+        bodies.insert(0, star);
+    }
+    bodies
+}
+
+/// Converts an sRGB colour component, as picked in an image editor, to linear rgb
+fn srgb_to_linear(c: f32) -> f32 {
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+/// Parses sRGB hex `rrggbb` or `r,g,b` with components in [0, 1], returning linear rgb
+fn parse_color(val: &str) -> Result<[f32; 3], String> {
+    let invalid = || format!("Invalid color `{}`, expected rrggbb or r,g,b", val);
+    let components = if val.contains(',') {
+        val.split(',')
+            .map(|c| match c.parse::<f32>() {
+                Ok(c) if c >= 0.0 && c <= 1.0 => Ok(c),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<f32>, String>>()?
+    }
+    else if val.len() == 6 && val.bytes().all(|b| b < 128) {
+        (0..3).map(|i| u8::from_str_radix(&val[i * 2..i * 2 + 2], 16)
+                .map(|c| f32::from(c) / 255.0)
+                .map_err(|_| invalid()))
+            .collect::<Result<Vec<f32>, String>>()?
+    }
+    else {
+        return Err(invalid());
+    };
+    match components.len() {
+        3 => Ok([srgb_to_linear(components[0]), srgb_to_linear(components[1]), srgb_to_linear(components[2])]),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod scenario_test {
+    use super::*;
+
+    #[test]
+    fn parse_bodies() {
+        let bodies = parse("
+            # x y vx vy mass radius
+            0.0 0.0 0.0 -1.0 1660 1.2
+            3.5 0.0   0.0 1.6 1000 0.9 # the second body
+        ").unwrap();
+
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0].mass, 1660.0);
+        assert_eq!(bodies[0].velocity.y, -1.0);
+        assert_eq!(bodies[1].center.x, 3.5);
+        assert_eq!(bodies[1].radius, 0.9);
+        assert!(bodies[0].id != bodies[1].id);
+        assert_eq!(bodies[0].color, palette_color(0));
+        assert_eq!(bodies[1].color, palette_color(1));
+    }
+
+    #[test]
+    fn parse_appearance() {
+        let bodies = parse("
+            0 0 0 0 1 1 color=ff8000 glow=0.5
+            1 0 0 0 1 1 color=0.25,1,0 texture=mars.png spin=-0.5
+        ").unwrap();
+
+        assert_eq!(bodies[0].color, [1.0, srgb_to_linear(128.0 / 255.0), 0.0]);
+        assert_eq!(bodies[0].glow, 0.5);
+        assert_eq!(bodies[1].color, [srgb_to_linear(0.25), 1.0, 0.0]);
+        assert_eq!(bodies[1].glow, 0.0);
+        assert_eq!(bodies[0].texture, None);
+        assert_eq!(bodies[1].texture, Some("mars.png".into()));
+        assert_eq!(bodies[1].spin_rate, -0.5);
+        assert_eq!(bodies[0].name, None);
+
+        let named = parse("0 0 0 0 1 1 name=Proxima_b").unwrap();
+        assert_eq!(named[0].name, Some("Proxima b".into()));
+        assert!(parse("0 0 0 0 1 1 name=").is_err());
+
+        assert!(parse("0 0 0 0 1 1 color=red").is_err());
+        assert!(parse("0 0 0 0 1 1 color=1,2,0").is_err());
+        assert!(parse("0 0 0 0 1 1 glow=-1").is_err());
+        assert!(parse("0 0 0 0 1 1 texture=").is_err());
+        assert!(parse("0 0 0 0 1 1 spin=fast").is_err());
+        assert_eq!(parse("0 0 0 0 1 1 mood=2").unwrap_err().reason, "Unknown key `mood`");
+    }
+
+    #[test]
+    fn colors_converted_from_srgb() {
+        // sRGB mid grey is about a fifth of full linear intensity
+        let grey = parse("0 0 0 0 1 1 color=808080").unwrap()[0].color;
+        assert!(grey.iter().all(|c| (c - 0.2158).abs() < 0.0001), "{:?}", grey);
+        assert_eq!(srgb_to_linear(0.0), 0.0);
+        assert_eq!(srgb_to_linear(1.0), 1.0);
+        // linear segment near black
+        assert_eq!(srgb_to_linear(0.04), 0.04 / 12.92);
+        assert!((srgb_to_linear(0.5) - 0.214).abs() < 0.001);
+    }
+
+    #[test]
+    fn benchmark_scene() {
+        let bodies = benchmark(1000);
+        assert_eq!(bodies.len(), 1000);
+        assert!(bodies.iter().skip(1).all(|b| b.center.x.hypot(b.center.y) > bodies[0].radius));
+        assert!(benchmark(0).is_empty());
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse("\n1 2 3 4 5").unwrap_err().line, 2);
+        assert_eq!(parse("1 2 3 4 five 6").unwrap_err().reason, "Invalid number `five`");
+        assert_eq!(parse("1 2 3 4 0 6").unwrap_err().line, 1);
+        assert_eq!(parse("# nothing").unwrap_err().line, 0);
+    }
+
+    #[test]
+    fn non_finite_rejected() {
+        assert_eq!(parse("NaN 0 0 0 1 1").unwrap_err().reason, "Invalid number `NaN`");
+        assert_eq!(parse("0 0 inf 0 1 1").unwrap_err().reason, "Invalid number `inf`");
+        assert_eq!(parse("0 0 0 0 inf 1").unwrap_err().reason, "Invalid number `inf`");
+        assert_eq!(parse("0 0 0 0 1 1 spin=-inf").unwrap_err().reason, "Invalid spin `-inf`");
+        assert_eq!(parse("0 0 0 0 1 1 glow=inf").unwrap_err().reason, "Invalid glow `inf`");
+    }
+}
@@ -8,11 +8,14 @@ use std::sync::mpsc;
 use rayon::prelude::*;
 use uuid::Uuid;
 use compute::compute_state;
+use cgmath::*;
 
 pub struct Seer {
     pub projection: single_value_channel::Receiver<Vec<OrbitCurve>>,
     pub main_deltas: mpsc::Sender<f64>,
     pub min_plot_distance: f64,
+    /// bodies of the co-rotating frame the curves are plotted in, if any
+    pub corotating: Option<(Uuid, Uuid)>,
 }
 
 pub const SEER_COMPUTE_DELTA: f64 = 0.001;
@@ -48,11 +51,15 @@ impl Seer {
         let (tx, main_deltas_receiver) = mpsc::channel();
         let (projection_get, projection) = single_value_channel::channel_starting_with(Vec::new());
 
-        let zoom = tasks.zoom.as_ref().map(|z| z.zoom_destination()).unwrap_or(initial_state.zoom);
+        let zoom = tasks.nearest_zoom(&initial_state);
         let min_plot_distance = Seer::min_plot_distance_at_zoom(zoom);
+        let corotation = tasks.active(&initial_state).corotate.clone();
+        let corotating = corotation.as_ref().map(|c| c.bodies);
 
         thread::spawn(move|| {
             let mut tasks = tasks.world_affecting();
+            // (pivot, angle) of the co-rotating frame at each plot
+            let mut frames = Vec::new();
             let mut plots = 0;
             let mut state = initial_state;
             let mut main_deltas_ahead = 0.0;
@@ -63,9 +70,13 @@ impl Seer {
             state.drawables.orbit_curves.clear();
             for body in &state.drawables.orbit_bodies {
                 let mut curve = OrbitCurve::new();
+                curve.color = body.color;
                 curve.plots.push(body.center);
                 state.drawables.orbit_curves.push(curve);
             }
+            if let Some(ref corotation) = corotation {
+                frames.push(corotation.frame(&state.drawables.orbit_bodies));
+            }
 
             let me = Uuid::new_v4();
             loop {
@@ -85,6 +96,8 @@ impl Seer {
                     for curve in &mut state.drawables.orbit_curves {
                         curve.remove_oldest_plots(outdated_plots as usize);
                     }
+                    let outdated_frames = (outdated_plots as usize).min(frames.len());
+                    frames.drain(..outdated_frames);
                 }
 
                 if plots >= SEER_MAX_PLOTS {
@@ -100,12 +113,21 @@ impl Seer {
                     let body = &state.drawables.orbit_bodies[idx];
                     curve.plots.push(body.center);
                 }
+                if let Some(ref corotation) = corotation {
+                    frames.push(corotation.frame(&state.drawables.orbit_bodies));
+                }
                 plots += 1;
 
                 if !filtering {
-                    let curves = state.drawables.orbit_curves.clone();
+                    let mut curves = state.drawables.orbit_curves.clone();
+                    let curve_frames = frames.clone();
                     let sender = tx.clone();
                     thread::spawn(move|| {
+                        if !curve_frames.is_empty() {
+                            curves = curves.par_iter()
+                                .map(|c| into_current_frame(c, &curve_frames))
+                                .collect();
+                        }
                         // filtering curves is quite intensive, so use another thread
                         let curves_for_render = curves.par_iter()
                             .map(|c| c.with_minimum_plot_distance(min_plot_distance))
@@ -132,6 +154,24 @@ impl Seer {
             projection: projection_get,
             main_deltas: tx,
             min_plot_distance,
+            corotating,
+        }
+    }
+}
+
+/// Transforms a curve's plots so each is positioned relative to the co-rotating frame
+/// at its own time, as seen from the frame at the first plot. Rendering with the current
+/// frame's view rotation then shows the curve as it moves in the rotating frame.
+fn into_current_frame(curve: &OrbitCurve, frames: &[Option<(Vector2<f64>, f64)>]) -> OrbitCurve {
+    let mut plots = Vec::with_capacity(curve.plots.len());
+    if let Some(&Some((current_pivot, current_angle))) = frames.first() {
+        for (plot, frame) in curve.plots.iter().zip(frames) {
+            match *frame {
+                Some((pivot, angle)) => plots.push(current_pivot + rotate(plot - pivot, current_angle - angle)),
+                // frame bodies are gone, further plots have no meaning in the frame
+                None => break,
+            }
         }
     }
+    OrbitCurve { plots, opacity: curve.opacity, color: curve.color, style: curve.style }
 }
@@ -1,52 +1,52 @@
 use cgmath::*;
 use OrbitBody;
+use orbitbody::palette_color;
 use orbitcurve::OrbitCurve;
 use debug::ComputeDebugInfo;
-use uuid::Uuid;
+use field::FieldMode;
+use arrows::ArrowScale;
+use camera::{Camera, Viewport, split_viewports};
 use std::f64;
+use uuid::Uuid;
 
 #[derive(Clone, Debug)]
 pub struct Drawables {
     pub orbit_bodies: Vec<OrbitBody>,
     pub orbit_curves: Vec<OrbitCurve>,
+    /// bodies of the co-rotating frame the curves are plotted in, if any
+    pub curves_corotating: Option<(Uuid, Uuid)>,
 }
 
 impl Drawables {
     fn initial() -> Drawables {
         let bodies = vec!(
             OrbitBody {
-                id: Uuid::new_v4(),
-                center: (0.0, 0.0).into(),
-                radius: 1.2,
-                mass: 1660.0,
-                velocity: (0.0, -1.0).into(),
+                name: Some("Alpha".into()),
+                color: palette_color(0),
+                glow: 0.8,
+                ..OrbitBody::new((0.0, 0.0).into(), (0.0, -1.0).into(), 1660.0, 1.2)
             },
             OrbitBody {
-                id: Uuid::new_v4(),
-                center: (3.5, 0.0).into(),
-                radius: 0.9,
-                mass: 1000.0,
-                velocity: (0.0, 1.6).into(),
+                name: Some("Beta".into()),
+                color: palette_color(1),
+                ..OrbitBody::new((3.5, 0.0).into(), (0.0, 1.6).into(), 1000.0, 0.9)
             },
             OrbitBody {
-                id: Uuid::new_v4(),
-                center: (9.0, 0.0).into(),
-                radius: 0.3,
-                mass: 1.0,
-                velocity: (0.0, 2.0).into(),
+                name: Some("Gamma".into()),
+                color: palette_color(2),
+                ..OrbitBody::new((9.0, 0.0).into(), (0.0, 2.0).into(), 1.0, 0.3)
             },
             OrbitBody {
-                id: Uuid::new_v4(),
-                center: (-12.0, 0.0).into(),
-                radius: 0.4,
-                mass: 2.0,
-                velocity: (0.0, -1.5).into(),
+                name: Some("Delta".into()),
+                color: palette_color(3),
+                ..OrbitBody::new((-12.0, 0.0).into(), (0.0, -1.5).into(), 2.0, 0.4)
             },
         );
 
         Drawables {
             orbit_bodies: bodies,
             orbit_curves: Vec::new(),
+            curves_corotating: None,
         }
     }
 
@@ -68,73 +68,109 @@ impl Drawables {
     }
 }
 
-fn birds_eye_at_z(height: f32) -> Matrix4<f32> {
-    let mut view = Matrix4::identity();
-    view.z.z = height;
-    view
+/// Rotates a vector anti-clockwise by an angle in radians
+pub fn rotate<S: BaseFloat>(vec: Vector2<S>, angle: S) -> Vector2<S> {
+    let (sin, cos) = angle.sin_cos();
+    Vector2::new(vec.x * cos - vec.y * sin, vec.x * sin + vec.y * cos)
 }
 
 #[derive(Clone, Debug)]
 pub struct State {
-    pub origin: Vector2<f32>,
-    pub zoom: f32,
+    /// camera of each viewport
+    pub cameras: Vec<Camera>,
+    /// index of the camera input controls, ie of the viewport under the cursor
+    pub active_camera: usize,
+    /// window size in pixels
     pub screen_width: u32,
     pub screen_height: u32,
-    pub view: Matrix4<f32>,
     pub user_quit: bool,
     pub drawables: Drawables,
     pub debug_info: ComputeDebugInfo,
     pub pause: bool,
     pub render_curves: bool,
+    pub render_trails: bool,
+    /// show the grid, scale bar & cursor coordinate
+    pub render_grid: bool,
+    /// velocity & acceleration arrows from each body
+    pub render_arrows: bool,
+    pub arrow_scale: ArrowScale,
+    /// gravitational field overlay
+    pub field_mode: FieldMode,
+    pub field_contours: bool,
+    pub render_labels: bool,
+    /// system overview inset
+    pub render_minimap: bool,
+    /// glow around luminous bodies
+    pub render_bloom: bool,
+    /// latest cursor screen position in pixels
+    pub cursor: (i32, i32),
+    pub show_bindings: bool,
+    /// detailed debug HUD
+    pub show_debug: bool,
+    /// number of the frame to save when capturing a camera path
+    pub capture_frame: Option<u32>,
 }
 
 impl State {
     pub fn new(screen_width: u32, screen_height: u32) -> State {
         State {
-            origin: Vector2::new(0.0f32, 0.0),
-            zoom: 16f32,
+            cameras: vec!(Camera::new(Viewport::fullscreen(screen_width, screen_height))),
+            active_camera: 0,
             screen_width,
             screen_height,
-            view: birds_eye_at_z(1.0),
             user_quit: false,
             drawables: Drawables::initial(),
             debug_info: ComputeDebugInfo::initial(),
             pause: false,
             render_curves: true,
+            render_trails: true,
+            render_grid: false,
+            render_arrows: false,
+            arrow_scale: ArrowScale::default(),
+            field_mode: FieldMode::Off,
+            field_contours: true,
+            cursor: (0, 0),
+            render_labels: true,
+            render_minimap: true,
+            render_bloom: true,
+            show_bindings: false,
+            show_debug: false,
+            capture_frame: None,
         }
     }
 
-    pub fn projection(&self) -> Matrix4<f32> {
-        ortho(self.origin.x - self.zoom * self.aspect_ratio(),
-              self.origin.x + self.zoom * self.aspect_ratio(),
-              self.origin.y - self.zoom,
-              self.origin.y + self.zoom,
-              1.0,
-              -1.0)
+    /// Camera input controls
+    pub fn camera(&self) -> &Camera {
+        &self.cameras[self.active_camera]
     }
 
-    pub fn aspect_ratio(&self) -> f32 {
-        self.screen_width as f32 / self.screen_height as f32
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.cameras[self.active_camera]
     }
 
-    /// translates screen pixels into world co-ordinates in the orthographic projection
-    pub fn screen_to_world_normalised<V: Into<Vector2<i32>>>(&self, pixels: V) -> Vector2<f32> {
-        let pixels = pixels.into();
-        let x_world = self.zoom * self.aspect_ratio() * (pixels.x as f32 * 2.0 / self.screen_width as f32 - 1f32);
-        let y_world = self.zoom * (-pixels.y as f32 * 2.0 / self.screen_height as f32 + 1f32);
-        Vector2::new(x_world, y_world)
+    /// Splits the window between `count` cameras, new cameras start as a copy of the active camera
+    pub fn split_cameras(&mut self, count: usize) {
+        let viewports = split_viewports(self.screen_width, self.screen_height, count);
+        let active = self.camera().clone();
+        self.cameras.truncate(viewports.len());
+        while self.cameras.len() < viewports.len() {
+            self.cameras.push(active.clone());
+        }
+        for (camera, viewport) in self.cameras.iter_mut().zip(viewports) {
+            camera.viewport = viewport;
+        }
+        self.active_camera = self.active_camera.min(self.cameras.len() - 1);
     }
 
-    pub fn screen_to_world<V: Into<Vector2<i32>>>(&self, pixels: V) -> Vector2<f32> {
-        self.origin + self.screen_to_world_normalised(pixels)
+    /// Index of the camera whose viewport contains a window pixel
+    pub fn camera_at(&self, pixel: (i32, i32)) -> Option<usize> {
+        self.cameras.iter().position(|camera| camera.viewport.contains(pixel))
     }
 
-    /// Returns tuple with (min, max) coord corners
-    /// - left: bottom left, least x & y visible world location
-    /// - right: top right, most x & y visible world location
-    pub fn visible_world_range(&self) -> (Vector2<f32>, Vector2<f32>) {
-        (self.screen_to_world(Vector2::new(0, self.screen_height as i32)),
-         self.screen_to_world(Vector2::new(self.screen_width as i32, 0)))
+    /// The body the active camera is following, if any
+    pub fn followed_body(&self) -> Option<&OrbitBody> {
+        let id = self.camera().following?;
+        self.drawables.orbit_bodies.iter().find(|b| b.id == id)
     }
 }
 
@@ -142,72 +178,19 @@ impl State {
 mod state_test {
     use super::*;
 
-    // see https://github.com/gfx-rs/gfx/tree/master/src/backend/gl
-    // (0,0)
-    //     ┌─┐
-    //     └─┘
-    //        (width-px, height-px)
-    //      |
-    //      v
-    // (-1az,1z)
-    //     ┌─┐
-    //     └─┘
-    //        (1az,-1z)
-    // :a aspect ratio
-    // :z zoom
-    fn test_screen_to_world(s: State) {
-        let a = s.aspect_ratio();
-        let z = s.zoom;
-        assert_eq!(s.screen_to_world(Vector2::new(0, 0)),
-            Vector2::new(-a * z, 1f32 * z), "top-left");
-        assert_eq!(s.screen_to_world(Vector2::new(s.screen_width as i32, 0)),
-            Vector2::new(a * z, 1f32 * z), "top-right");
-        assert_eq!(s.screen_to_world(Vector2::new(0, s.screen_height as i32)),
-            Vector2::new(-a * z, -1f32 * z), "bottom-left");
-        assert_eq!(s.screen_to_world(Vector2::new(s.screen_width as i32, s.screen_height as i32)),
-            Vector2::new(a * z, -1f32 * z), "bottom-right");
-        assert_eq!(s.screen_to_world(Vector2::new(s.screen_width as i32 / 2, s.screen_height as i32 / 2 )),
-            Vector2::new(0f32, 0f32), "center");
-    }
-    #[test]
-    fn screen_to_world_zoom_1_aspect_1() {
-        //      | simplifies to
-        //      v
-        // (-1,1)
-        //     ┌─┐
-        //     └─┘
-        //        (1,-1)
-        test_screen_to_world(State::new(100, 100));
-    }
-
-    #[test]
-    fn screen_to_world_zoom_1() {
-        //      | simplifies to
-        //      v
-        // (-1a,1)
-        //     ┌─┐
-        //     └─┘
-        //        (1a,-1)
-        test_screen_to_world(State::new(160, 90));
-    }
-
-    #[test]
-    fn screen_to_world() {
-        //      | simplifies to
-        //      v
-        // (-1a,1)
-        //     ┌─┐
-        //     └─┘
-        //        (1a,-1)
-        let mut state = State::new(160, 90);
-        state.zoom = 0.33f32;
-        test_screen_to_world(state);
-    }
-
     #[test]
-    fn visible_world_range() {
-        let mut state = State::new(180, 90);
-        state.zoom = 3f32;
-        assert_eq!(state.visible_world_range(), ((-6_f32, -3_f32).into(), (6_f32, 3_f32).into()));
+    fn split_cameras_copy_active_camera() {
+        let mut state = State::new(200, 100);
+        state.camera_mut().zoom = 4.0;
+        state.split_cameras(2);
+        assert_eq!(state.cameras.len(), 2);
+        assert_eq!(state.cameras[1].zoom, 4.0);
+        assert_eq!(state.cameras[1].viewport, Viewport { left: 100, top: 0, width: 100, height: 100 });
+        assert_eq!(state.camera_at((150, 50)), Some(1));
+
+        state.active_camera = 1;
+        state.split_cameras(1);
+        assert_eq!(state.active_camera, 0);
+        assert_eq!(state.camera().viewport, Viewport::fullscreen(200, 100));
     }
 }